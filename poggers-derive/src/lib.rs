@@ -3,8 +3,22 @@ use proc_macro2::Span;
 use proc_macro_crate::crate_name;
 // use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
-use syn::{parse::Parse, parse_macro_input, punctuated::Punctuated, Ident, ItemFn, Token};
+use syn::{
+    parse::Parse, parse_macro_input, punctuated::Punctuated, spanned::Spanned, Data, DeriveInput,
+    Fields, Ident, ItemFn, LitInt, Token,
+};
 
+fn poggers_crate() -> proc_macro2::TokenStream {
+    match crate_name("poggers").expect("poggers-derive to be found") {
+        proc_macro_crate::FoundCrate::Itself => quote!(crate),
+        proc_macro_crate::FoundCrate::Name(x) => {
+            let i = Ident::new(&x, Span::call_site());
+            quote!(#i)
+        }
+    }
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
 struct CreateEntryArguments {
     no_console: bool,
     no_thread: bool,
@@ -50,16 +64,12 @@ pub fn create_entry(attr: TokenStream, item: TokenStream) -> TokenStream {
     let inputb = input.clone();
     #[cfg(target_os = "windows")]
     let arg = parse_macro_input!(attr as CreateEntryArguments);
+    #[cfg(not(target_os = "windows"))]
+    let _ = attr;
     let input_name = input.sig.ident;
     let has_hmd = !input.sig.inputs.is_empty();
 
-    let curr_crate = match crate_name("poggers").expect("poggers-derive to be found") {
-        proc_macro_crate::FoundCrate::Itself => quote!(crate),
-        proc_macro_crate::FoundCrate::Name(x) => {
-            let i = Ident::new(&x, Span::call_site());
-            quote!(#i)
-        }
-    };
+    let curr_crate = poggers_crate();
 
     let ret = input.sig.output;
 
@@ -167,3 +177,225 @@ pub fn create_entry(attr: TokenStream, item: TokenStream) -> TokenStream {
         #generated
     })
 }
+
+/// a field of a [RemoteStruct] and where it lives relative to the struct
+struct RemoteField {
+    ident: Ident,
+    ty: syn::Type,
+    offset: LitInt,
+    pointer: bool,
+}
+
+impl RemoteField {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut offset = None;
+        let mut pointer = false;
+        for attr in &field.attrs {
+            if attr.path().is_ident("offset") {
+                let lit: LitInt = attr.parse_args()?;
+                lit.base10_parse::<usize>()?;
+                offset = Some(lit);
+            } else if attr.path().is_ident("pointer") {
+                attr.meta.require_path_only()?;
+                pointer = true;
+            }
+        }
+        Ok(RemoteField {
+            ident: field.ident.clone().expect("named field"),
+            ty: field.ty.clone(),
+            offset: offset.ok_or_else(|| {
+                syn::Error::new(field.span(), "missing #[offset(..)] on remote struct field")
+            })?,
+            pointer,
+        })
+    }
+}
+
+/// This macro generates methods to read a struct out of another process field by field.
+/// every field needs an `#[offset(..)]` from the start of the struct, fields marked with `#[pointer]`
+/// are read from the address stored at the offset instead, using the pointer width of the process.
+/// the type of every field must implement `poggers::traits::Pod`.
+/// ```ignore
+/// #[derive(poggers_derive::RemoteStruct)]
+/// struct Player {
+///     #[offset(0x10)]
+///     health: f32,
+///     #[offset(0x48)]
+///     #[pointer]
+///     team: u32,
+/// }
+/// let player = unsafe { Player::read_from(&process, player_addr)? };
+/// unsafe { Player::set_health(&process, player_addr, &100.0)? };
+/// ```
+/// ## Generated
+/// * `read_from(&impl Mem, addr)` reads every field into a new struct
+/// * `get_<field>(&impl Mem, addr)` reads a single field
+/// * `set_<field>(&impl Mem, addr, &value)` writes a single field
+#[proc_macro_derive(RemoteStruct, attributes(offset, pointer))]
+pub fn remote_struct(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    match expand_remote_struct(input) {
+        Ok(x) => x.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand_remote_struct(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "RemoteStruct can only be derived for structs",
+        ));
+    };
+    let Fields::Named(named) = &data.fields else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "RemoteStruct requires named fields",
+        ));
+    };
+    let fields = named
+        .named
+        .iter()
+        .map(RemoteField::parse)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let curr_crate = poggers_crate();
+    let mem = quote!(#curr_crate::traits::Mem);
+    let error = quote!(#curr_crate::traits::MemError);
+    let vis = &input.vis;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let accessors = fields.iter().map(|field| {
+        let RemoteField {
+            ident,
+            ty,
+            offset,
+            pointer,
+        } = field;
+        let getter = Ident::new(&format!("get_{}", ident), ident.span());
+        let setter = Ident::new(&format!("set_{}", ident), ident.span());
+        let target = if *pointer {
+            quote!(mem.read_ptr(addr + #offset)?)
+        } else {
+            quote!(addr + #offset)
+        };
+        let get_doc = format!("read `{}` of the struct at <addr>", ident);
+        let set_doc = format!("write `{}` of the struct at <addr>", ident);
+        quote! {
+            #[doc = #get_doc]
+            /// # Safety
+            /// reads memory of the process at the field
+            #[allow(dead_code)]
+            #vis unsafe fn #getter<__M: #mem>(mem: &__M, addr: usize) -> Result<#ty, #error> {
+                mem.read::<#ty>(#target)
+            }
+            #[doc = #set_doc]
+            /// # Safety
+            /// writes memory of the process at the field
+            #[allow(dead_code)]
+            #vis unsafe fn #setter<__M: #mem>(mem: &__M, addr: usize, value: &#ty) -> Result<(), #error> {
+                mem.write::<#ty>(#target, value)
+            }
+        }
+    });
+    let reads = fields.iter().map(|field| {
+        let ident = &field.ident;
+        let getter = Ident::new(&format!("get_{}", ident), ident.span());
+        quote!(#ident: Self::#getter(mem, addr)?)
+    });
+
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// read every field of the struct at <addr>
+            /// # Safety
+            /// reads memory of the process at every field
+            #[allow(dead_code)]
+            #vis unsafe fn read_from<__M: #mem>(mem: &__M, addr: usize) -> Result<Self, #error> {
+                Ok(Self {
+                    #(#reads,)*
+                })
+            }
+            #(#accessors)*
+        }
+    })
+}
+
+/// This macro implements `poggers::traits::Pod` for a struct, allowing it to be read with the safe
+/// `SafeMem` reads. the struct must be `#[repr(C)]` or `#[repr(transparent)]`, every field must be
+/// `Pod` and there must not be any padding between or after the fields, which is checked at compile
+/// time.
+/// ```ignore
+/// #[derive(Clone, Copy, poggers_derive::Pod)]
+/// #[repr(C)]
+/// struct Vec3 {
+///     x: f32,
+///     y: f32,
+///     z: f32,
+/// }
+/// let position = process.read_value::<Vec3>(player + 0x30)?;
+/// ```
+#[proc_macro_derive(Pod)]
+pub fn pod(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    match expand_pod(input) {
+        Ok(x) => x.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand_pod(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "Pod can only be derived for structs",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "Pod can't be derived for generic structs",
+        ));
+    }
+    let mut has_repr = false;
+    for attr in input.attrs.iter().filter(|x| x.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") || meta.path.is_ident("transparent") {
+                has_repr = true;
+            }
+            if meta.input.peek(syn::token::Paren) {
+                // packed(N) / align(N)
+                let _ = meta.input.parse::<proc_macro2::Group>()?;
+            }
+            Ok(())
+        })?;
+    }
+    if !has_repr {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "Pod requires #[repr(C)] or #[repr(transparent)]",
+        ));
+    }
+
+    let curr_crate = poggers_crate();
+    let name = &input.ident;
+    let types = data
+        .fields
+        .iter()
+        .map(|field| &field.ty)
+        .collect::<Vec<_>>();
+    Ok(quote! {
+        const _: () = {
+            fn assert_pod<T: #curr_crate::traits::Pod>() {}
+            #[allow(dead_code)]
+            fn assert_fields() {
+                #(assert_pod::<#types>();)*
+            }
+            assert!(
+                ::std::mem::size_of::<#name>() == 0 #(+ ::std::mem::size_of::<#types>())*,
+                concat!(stringify!(#name), " has padding and can't be Pod")
+            );
+        };
+        unsafe impl #curr_crate::traits::Pod for #name {}
+    })
+}
@@ -0,0 +1,65 @@
+//! compares the streaming matcher, the memchr anchored matcher and the multi-threaded scan on a
+//! large module. the parallel scan only pulls ahead with several cores to spread the chunks over.
+//! run with `cargo bench -p poggers --features mock`
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use poggers::{
+    sigscan::pattern::Pattern,
+    structures::{
+        mock::MockProcess, process::implement::utils::ProcessUtils, protections::Protections,
+    },
+};
+
+const MODULE_BASE: usize = 0x1_0000_0000;
+const MODULE_SIZE: usize = 0x1000_0000;
+const SIGNATURE: [u8; 10] = [0x48, 0x8B, 0x05, 0x10, 0x20, 0x30, 0x40, 0x48, 0x85, 0xC0];
+
+#[cfg(unix)]
+fn read_only() -> Protections {
+    Protections::new().with_read(true)
+}
+#[cfg(windows)]
+fn read_only() -> Protections {
+    Protections::ReadOnly
+}
+
+/// noisy code-like bytes with the signature only at the very end
+fn module_data() -> Vec<u8> {
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let mut data = (0..MODULE_SIZE)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect::<Vec<_>>();
+    let at = MODULE_SIZE - 0x100;
+    data[at..at + SIGNATURE.len()].copy_from_slice(&SIGNATURE);
+    data
+}
+
+fn scan(c: &mut Criterion) {
+    let data = module_data();
+    let pattern = Pattern::parse("48 8B 05 ? ? ? ? 48 85 C0").unwrap();
+    let process =
+        MockProcess::new("game").with_module("libgame.so", MODULE_BASE, data.clone(), read_only());
+    let module = process.get_module("libgame.so").unwrap();
+
+    let mut group = c.benchmark_group("scan");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(MODULE_SIZE as u64));
+    group.bench_function("find_in", |b| {
+        b.iter(|| pattern.find_in(data.iter().copied()))
+    });
+    group.bench_function("find", |b| b.iter(|| pattern.find(&data)));
+    group.bench_function("module_scan_all", |b| {
+        b.iter(|| module.scan_all(&pattern).unwrap().count())
+    });
+    group.bench_function("module_scan_all_parallel", |b| {
+        b.iter(|| module.scan_all_parallel(&pattern, 0).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, scan);
+criterion_main!(benches);
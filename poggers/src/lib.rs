@@ -12,7 +12,7 @@
 //! ```toml
 //! [dependencies]
 //! poggers = "1"
-//! # if you need to use the entrypoint macro
+//! # if you need to use the entrypoint or RemoteStruct macros
 //! poggers_derive = "0.1.5"
 //! ```
 //!  ## Common Structs
@@ -22,17 +22,18 @@
 //!  ## Common Traits
 //!  * [`Mem`](traits::Mem) - A trait which allows a struct to read and write to memory.
 //!  * [`SigScan`](sigscan::SigScan) - A trait which allows a struct to sig scan.
+//!  * [`SafeMem`](traits::SafeMem) - Safe reads and writes of [`Pod`](traits::Pod) types for external processes.
 //!  ## Example External usage:
-//! ```
+//! ```no_run
 //!  use poggers::structures::process::Process;
 //!  use poggers::traits::Mem;
-//!  let process = Process::find_name("csgo.exe")?;
+//!  let process = Process::find_name("csgo.exe").unwrap();
 //!  unsafe {
 //!     process.write(0x1000,&1).unwrap()
 //!  }
 //! ```
 //!  ## Example Internal usage:
-//! ```
+//! ```ignore
 //! use poggers::structures::process::implement::utils::ProcessUtils;
 //! use poggers::structures::process::Process;
 //! use poggers::traits::Mem;
@@ -42,7 +43,7 @@
 //! fn entry() {
 //!     let this_proc = Process::this_process();
 //!     unsafe {
-//!         let bleh : i32 = this_proc.read(0x1000).?;
+//!         let bleh : i32 = this_proc.read(0x1000).unwrap();
 //!         println!("{}",bleh);
 //!         let base_mod_name = this_proc.get_base_module().unwrap().get_name();
 //!         println!("{}",base_mod_name);
@@ -0,0 +1,397 @@
+/// compiled signatures
+pub mod pattern;
+
+use std::{
+    borrow::Cow,
+    ops::Range,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use self::pattern::{Pattern, PatternError, StreamMatches, ToPattern};
+use super::{
+    structures::region::RegionFilter,
+    traits::{Mem, MemError},
+};
+
+/// memory is scanned in chunks of this size, overlapping by the pattern length
+const SCAN_CHUNK_SIZE: usize = 0x100_0000;
+/// The trait which allows a class to sig scan.
+/// # Notes
+/// Requires the [`Mem`] trait to be implemented.
+/// # Functions
+/// * [`SigScan::scan`] will read for each byte in the size
+/// * [`SigScan::scan_all`] and [`SigScan::scan_unique`] check every match instead of the first
+/// * [`SigScan::scan_process_all`] scans every readable region of the process, optionally on
+///   multiple threads with [`SigScan::scan_process_all_parallel`]
+/// * [`SigScan::scan_process_filtered`] scans only the regions matching a
+///   [`RegionFilter`], such as JIT code or the heap
+/// * [`SigScan::scan_batch_value`] scan for a value instead of a signature (not really recommended
+///   unless u know what you are doing)
+pub trait SigScan: Mem {
+    /// Scans for a pattern in the process.
+    /// # Arguments
+    /// * `pattern` - The pattern to scan for, either a `&str` or a compiled [Pattern](pattern::Pattern).
+    /// * `iter` the iterator to scan
+    /// # Returns
+    /// * [Option<usize>] - The offset in the iterator where the pattern was found.
+    /// * [PatternError] - if the pattern could not be parsed.
+    fn scan<'a>(
+        &self,
+        pattern: impl ToPattern,
+        iter: impl Iterator<Item = &'a u8>,
+    ) -> Result<Option<usize>, PatternError> {
+        Ok(pattern.to_pattern()?.find_in(iter.copied()))
+    }
+    /// Scans for every match of a pattern, including overlapping ones.
+    /// ```rs
+    /// let call_sites = process.scan_all("E8 ? ? ? ? 84 C0", data.iter())?.collect::<Vec<_>>();
+    /// ```
+    /// # Returns
+    /// * an iterator of the offsets in the iterator where the pattern was found.
+    /// * [PatternError] - if the pattern could not be parsed.
+    fn scan_all<'a, I: Iterator<Item = &'a u8>>(
+        &self,
+        pattern: impl ToPattern,
+        iter: I,
+    ) -> Result<StreamMatches<'static, std::iter::Copied<I>>, PatternError> {
+        let pattern = pattern.to_pattern()?.into_owned();
+        Ok(StreamMatches::new(Cow::Owned(pattern), iter.copied()))
+    }
+    /// Scans for a pattern which has to match exactly once.
+    /// # Returns
+    /// * the offset in the iterator where the pattern was found.
+    /// * [MemError::PatternNotFound] or [MemError::PatternNotUnique] with the amount of matches.
+    fn scan_unique<'a>(
+        &self,
+        pattern: impl ToPattern,
+        iter: impl Iterator<Item = &'a u8>,
+    ) -> Result<usize, MemError> {
+        unique(self.scan_all(pattern, iter)?)
+    }
+    /// Scans every readable region of the process for a pattern, regions which fail to read are
+    /// skipped.
+    /// # Returns
+    /// * an iterator of the addresses where the pattern was found.
+    /// # Safety
+    /// reads every readable region of the process.
+    unsafe fn scan_process_all(
+        &self,
+        pattern: impl ToPattern,
+    ) -> Result<impl Iterator<Item = usize> + '_, MemError>
+    where
+        Self: Sized,
+    {
+        self.scan_process_filtered(pattern, RegionFilter::new())
+    }
+    /// Scans every readable region of the process for a pattern like
+    /// [scan_process_all](SigScan::scan_process_all), splitting the work across <threads>
+    /// threads, or one per core if 0.
+    /// # Returns
+    /// * the sorted addresses where the pattern was found.
+    /// # Safety
+    /// reads every readable region of the process.
+    unsafe fn scan_process_all_parallel(
+        &self,
+        pattern: impl ToPattern,
+        threads: usize,
+    ) -> Result<Vec<usize>, MemError>
+    where
+        Self: Sized + Sync,
+    {
+        self.scan_process_filtered_parallel(pattern, RegionFilter::new(), threads)
+    }
+    /// Scans the readable regions of the process matching <filter> for a pattern, such as only
+    /// executable anonymous memory to find JIT code, or only the heap.
+    /// ```rs
+    /// let filter = RegionFilter::new().with_writable(true).with_file_backed(false);
+    /// let found = unsafe { process.scan_process_filtered("DE AD BE EF", filter)? };
+    /// ```
+    /// # Returns
+    /// * an iterator of the addresses where the pattern was found.
+    /// # Safety
+    /// reads every readable region of the process matching the filter.
+    unsafe fn scan_process_filtered(
+        &self,
+        pattern: impl ToPattern,
+        filter: RegionFilter,
+    ) -> Result<impl Iterator<Item = usize> + '_, MemError>
+    where
+        Self: Sized,
+    {
+        let pattern = pattern.to_pattern()?.into_owned();
+        let chunks = readable_chunks(self, &filter)?;
+        Ok(chunks
+            .into_iter()
+            .flat_map(move |chunk| chunk.scan(self, &pattern)))
+    }
+    /// Scans the readable regions of the process matching <filter> for a pattern like
+    /// [scan_process_filtered](SigScan::scan_process_filtered), splitting the work across
+    /// <threads> threads, or one per core if 0.
+    /// # Returns
+    /// * the sorted addresses where the pattern was found.
+    /// # Safety
+    /// reads every readable region of the process matching the filter.
+    unsafe fn scan_process_filtered_parallel(
+        &self,
+        pattern: impl ToPattern,
+        filter: RegionFilter,
+        threads: usize,
+    ) -> Result<Vec<usize>, MemError>
+    where
+        Self: Sized + Sync,
+    {
+        let pattern = pattern.to_pattern()?;
+        let chunks = readable_chunks(self, &filter)?;
+        Ok(scan_parallel(self, &pattern, &chunks, threads))
+    }
+    /// scans for a value in a page
+    fn scan_batch_value<T: Sized>(&self, val: &T, page: &[u8]) -> Option<usize> {
+        let type_size = std::mem::size_of::<T>();
+        let mut val_arr = vec![0; type_size];
+        unsafe {
+            (val as *const T as *const u8).copy_to_nonoverlapping(val_arr.as_mut_ptr(), type_size)
+        };
+        for (i, val) in page.chunks(type_size).enumerate() {
+            // println!("val in mem :{:X?} - looking for: {:X?}", &val, &val_arr);
+            if val == val_arr {
+                return Some(i * type_size);
+            }
+        }
+        None
+    }
+}
+
+/// the only match of <matches>, or how many there were
+pub(crate) fn unique(mut matches: impl Iterator<Item = usize>) -> Result<usize, MemError> {
+    let first = matches.next().ok_or(MemError::PatternNotFound)?;
+    match matches.count() {
+        0 => Ok(first),
+        rest => Err(MemError::PatternNotUnique(rest + 1)),
+    }
+}
+
+/// a piece of memory which is read and scanned at once. matches have to start before `end` but may
+/// run on until `limit`, so matches crossing into the next chunk are found.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ScanChunk {
+    start: usize,
+    end: usize,
+    limit: usize,
+}
+
+/// splits <range> into chunks of [SCAN_CHUNK_SIZE]
+pub(crate) fn scan_chunks(range: Range<usize>) -> impl Iterator<Item = ScanChunk> {
+    let limit = range.end;
+    range.step_by(SCAN_CHUNK_SIZE).map(move |start| ScanChunk {
+        start,
+        end: limit.min(start.saturating_add(SCAN_CHUNK_SIZE)),
+        limit,
+    })
+}
+
+impl ScanChunk {
+    /// the addresses of every match of <pattern> starting in the chunk, none if it fails to read
+    /// # Safety
+    /// reads the memory of the process within the chunk.
+    pub(crate) unsafe fn scan<T: Mem>(&self, process: &T, pattern: &Pattern) -> Vec<usize> {
+        let read_end = self.limit.min(self.end.saturating_add(pattern.len() - 1));
+        let Ok(data) = process.read_sized(self.start, read_end - self.start) else {
+            return vec![];
+        };
+        pattern
+            .find_iter(&data)
+            .map(|offset| self.start + offset)
+            .take_while(|addr| *addr < self.end)
+            .collect()
+    }
+}
+
+/// the readable memory of <process> matching <filter> split into [ScanChunk]s
+pub(crate) fn readable_chunks<T: Mem>(
+    process: &T,
+    filter: &RegionFilter,
+) -> Result<Vec<ScanChunk>, MemError> {
+    Ok(process
+        .regions()?
+        .filter(|region| region.is_readable())
+        .filter_map(|region| filter.clamp(&region))
+        .flat_map(scan_chunks)
+        .collect())
+}
+
+/// scans <chunks> on <threads> threads, or one per core if 0. returns the sorted matches.
+/// # Safety
+/// reads the memory of the process within every chunk.
+pub(crate) unsafe fn scan_parallel<T: Mem + Sync>(
+    process: &T,
+    pattern: &Pattern,
+    chunks: &[ScanChunk],
+    threads: usize,
+) -> Vec<usize> {
+    let threads = match threads {
+        0 => std::thread::available_parallelism().map_or(1, |x| x.get()),
+        x => x,
+    }
+    .min(chunks.len().max(1));
+    let next = AtomicUsize::new(0);
+    let mut found = std::thread::scope(|scope| {
+        let workers = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut found = vec![];
+                    while let Some(chunk) = chunks.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        found.extend(unsafe { chunk.scan(process, pattern) });
+                    }
+                    found
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("scan thread panicked"))
+            .collect::<Vec<_>>()
+    });
+    found.sort_unstable();
+    found
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{SigScan, SCAN_CHUNK_SIZE};
+    use crate::structures::region::RegionFilter;
+    use crate::{
+        structures::{
+            mock::MockProcess, process::implement::utils::ProcessUtils, protections::Protections,
+        },
+        traits::MemError,
+    };
+
+    #[test]
+    fn test_scan_all() {
+        let read = Protections::new().with_read(true);
+        let mut code = vec![0u8; 0x1000];
+        for at in [0x10, 0x200, 0x800] {
+            code[at..at + 3].copy_from_slice(&[0xE8, 0x01, 0xC3]);
+        }
+        code[0x400..0x403].copy_from_slice(&[0x55, 0x48, 0x89]);
+        // a match crossing the boundary between two scan chunks
+        let mut heap = vec![0u8; SCAN_CHUNK_SIZE + 0x1000];
+        heap[SCAN_CHUNK_SIZE - 1..SCAN_CHUNK_SIZE + 2].copy_from_slice(&[0xE8, 0x02, 0xC3]);
+        let proc = MockProcess::new("game")
+            .with_module("libgame.so", 0x400000, code.clone(), read)
+            .with_region(0x1000_0000, heap, read)
+            .with_region(0x2000_0000, vec![0xE8, 0x03, 0xC3], Protections::new());
+
+        assert_eq!(
+            proc.scan_all("E8 ? C3", code.iter())
+                .unwrap()
+                .collect::<Vec<_>>(),
+            [0x10, 0x200, 0x800]
+        );
+        assert_eq!(proc.scan_unique("55 48 89", code.iter()).unwrap(), 0x400);
+        assert!(matches!(
+            proc.scan_unique("E8 ? C3", code.iter()),
+            Err(MemError::PatternNotUnique(3))
+        ));
+        assert!(matches!(
+            proc.scan_unique("90 90", code.iter()),
+            Err(MemError::PatternNotFound)
+        ));
+
+        let module = proc.get_module("libgame.so").unwrap();
+        assert_eq!(module.scan_all("E8 ? C3").unwrap().count(), 3);
+        assert_eq!(module.scan_unique("55 48 89").unwrap(), 0x400400);
+        assert!(matches!(
+            module.scan_all("E8 ?Z"),
+            Err(MemError::InvalidPattern(_))
+        ));
+
+        let found = unsafe { proc.scan_process_all("E8 ? C3").unwrap() }.collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [
+                0x400010,
+                0x400200,
+                0x400800,
+                0x1000_0000 + SCAN_CHUNK_SIZE - 1
+            ]
+        );
+        for threads in [0, 1, 3] {
+            assert_eq!(
+                unsafe { proc.scan_process_all_parallel("E8 ? C3", threads).unwrap() },
+                found
+            );
+        }
+        assert_eq!(
+            module.scan_all_parallel("E8 ? C3", 2).unwrap(),
+            [0x400010, 0x400200, 0x400800]
+        );
+    }
+
+    #[test]
+    fn test_scan_filtered() {
+        let code = Protections::new().with_read(true).with_execute(true);
+        let data = Protections::new().with_read(true).with_write(true);
+        let sig = [0x55, 0x48, 0x8B, 0xEC];
+        let proc = MockProcess::new("game")
+            .with_module("libgame.so", 0x400000, sig.repeat(0x400), code)
+            .with_module("libother.so", 0x800000, sig.repeat(0x400), data)
+            .with_region(0x1000_0000, sig.repeat(0x400), code)
+            .with_region(0x2000_0000, sig.repeat(0x400), data);
+        let scan = |filter: RegionFilter| {
+            let found = unsafe { proc.scan_process_filtered("55 48 8B EC", filter.clone()) }
+                .unwrap()
+                .collect::<Vec<_>>();
+            assert_eq!(
+                unsafe { proc.scan_process_filtered_parallel("55 48 8B EC", filter, 2) }.unwrap(),
+                found
+            );
+            // the first match of every region is enough to tell them apart
+            found
+                .into_iter()
+                .filter(|x| x % 0x1000 == 0)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            scan(RegionFilter::new()),
+            [0x400000, 0x800000, 0x1000_0000, 0x2000_0000]
+        );
+        // jit code and anonymous data which belong to no module
+        assert_eq!(
+            scan(
+                RegionFilter::new()
+                    .with_executable(true)
+                    .with_file_backed(false)
+            ),
+            [0x1000_0000]
+        );
+        assert_eq!(
+            scan(
+                RegionFilter::new()
+                    .with_writable(true)
+                    .with_file_backed(false)
+            ),
+            [0x2000_0000]
+        );
+        assert_eq!(
+            scan(
+                RegionFilter::new()
+                    .with_module("libother.so")
+                    .with_module("libgame.so")
+            ),
+            [0x400000, 0x800000]
+        );
+        assert_eq!(scan(RegionFilter::new().with_private(false)), []);
+        // regions crossing the range are cut down, matches have to lie entirely within it
+        let found = unsafe {
+            proc.scan_process_filtered(
+                "55 48 8B EC",
+                RegionFilter::new().with_range(0x400002, 0x400010),
+            )
+        }
+        .unwrap()
+        .collect::<Vec<_>>();
+        assert_eq!(found, [0x400004, 0x400008, 0x40000C]);
+    }
+}
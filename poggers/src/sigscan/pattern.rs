@@ -0,0 +1,450 @@
+use std::{borrow::Cow, fmt::Display, str::FromStr};
+
+use thiserror::Error;
+
+/// a signature parsed once and reusable for any amount of scans.
+/// written as space separated hex bytes with `?` or `??` for bytes which can be anything,
+/// such as `48 8B 05 ? ? ? ? 48 85 C0`, or code style with [Pattern::from_code].
+/// ```rs
+/// let pattern = Pattern::parse("48 8B 05 ? ? ? ? 48 85 C0")?;
+/// let found = process.scan(&pattern, data.iter())?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Pattern {
+    pub(crate) bytes: Vec<u8>,
+    /// the bits of each byte which have to match, 0 for wildcards
+    pub(crate) mask: Vec<u8>,
+    /// the index of the byte searched for with memchr before checking the whole pattern, none if
+    /// no byte is fully known
+    pub(crate) anchor: Option<usize>,
+}
+
+/// errors from parsing a [Pattern]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PatternError {
+    /// the pattern has no bytes
+    #[error("empty pattern")]
+    Empty,
+    /// a token is not hex or a wildcard
+    #[error("invalid byte '{token}' at {pos}")]
+    InvalidHex {
+        /// offset of the token in the pattern
+        pos: usize,
+        /// the token which could not be parsed
+        token: String,
+    },
+    /// a token has an odd amount of hex digits, so its last byte is missing a nibble
+    #[error("trailing nibble at {0}")]
+    TrailingNibble(usize),
+    /// the mask of a code style pattern doesn't cover every byte
+    #[error("{mask} mask characters for {bytes} bytes")]
+    MaskLength {
+        /// the amount of bytes
+        bytes: usize,
+        /// the amount of mask characters
+        mask: usize,
+    },
+    /// a mask character is neither `x` nor `?`
+    #[error("invalid mask character '{1}' at {0}")]
+    InvalidMask(usize, char),
+}
+
+/// values which can be used as a pattern, so scans accept either a `&str` or a compiled
+/// [Pattern]
+pub trait ToPattern {
+    /// compile the pattern, borrowing it if it already is one
+    fn to_pattern(&self) -> Result<Cow<'_, Pattern>, PatternError>;
+}
+
+impl ToPattern for Pattern {
+    fn to_pattern(&self) -> Result<Cow<'_, Pattern>, PatternError> {
+        Ok(Cow::Borrowed(self))
+    }
+}
+impl ToPattern for str {
+    fn to_pattern(&self) -> Result<Cow<'_, Pattern>, PatternError> {
+        Pattern::parse(self).map(Cow::Owned)
+    }
+}
+impl ToPattern for String {
+    fn to_pattern(&self) -> Result<Cow<'_, Pattern>, PatternError> {
+        self.as_str().to_pattern()
+    }
+}
+impl<T: ToPattern + ?Sized> ToPattern for &T {
+    fn to_pattern(&self) -> Result<Cow<'_, Pattern>, PatternError> {
+        (**self).to_pattern()
+    }
+}
+
+impl Pattern {
+    /// parse an IDA or x64dbg style pattern such as `48 8B ? ? 90` or `48 8B ?? ?? 90`.
+    /// single nibbles can be wildcards as well, `4? ?F`.
+    pub fn parse(pattern: &str) -> Result<Self, PatternError> {
+        let mut bytes = vec![];
+        let mut mask = vec![];
+        let mut pos = 0;
+        for token in pattern.split_ascii_whitespace() {
+            // split_ascii_whitespace doesn't report offsets, so find the token again
+            pos += pattern[pos..].find(token).unwrap_or(0);
+            if token == "?" {
+                bytes.push(0);
+                mask.push(0);
+            } else {
+                if !token.bytes().all(|x| x.is_ascii_hexdigit() || x == b'?') {
+                    return Err(PatternError::InvalidHex {
+                        pos,
+                        token: token.to_string(),
+                    });
+                }
+                if token.len() % 2 != 0 {
+                    return Err(PatternError::TrailingNibble(pos + token.len() - 1));
+                }
+                // tokens may hold several bytes, `488B05`
+                for pair in token.as_bytes().chunks(2) {
+                    let (high, high_mask) = nibble(pair[0]);
+                    let (low, low_mask) = nibble(pair[1]);
+                    bytes.push(high << 4 | low);
+                    mask.push(high_mask << 4 | low_mask);
+                }
+            }
+            pos += token.len();
+        }
+        Self::from_masked(bytes, mask)
+    }
+    /// build a pattern out of code style bytes and a mask with `x` for bytes which have to match
+    /// and `?` for wildcards, such as `b"\x48\x8B\x00"` and `"xx?"`
+    pub fn from_code(bytes: impl AsRef<[u8]>, mask: &str) -> Result<Self, PatternError> {
+        let bytes = bytes.as_ref();
+        if bytes.len() != mask.len() {
+            return Err(PatternError::MaskLength {
+                bytes: bytes.len(),
+                mask: mask.len(),
+            });
+        }
+        let mask = mask
+            .chars()
+            .enumerate()
+            .map(|(pos, x)| match x {
+                'x' | 'X' => Ok(0xFF),
+                '?' | '.' => Ok(0),
+                _ => Err(PatternError::InvalidMask(pos, x)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_masked(bytes.to_vec(), mask)
+    }
+    /// parse code style bytes written out as text, such as `\x48\x8B\x00` with the mask `xx?`,
+    /// as copied out of C or C++ sources
+    pub fn parse_code(bytes: &str, mask: &str) -> Result<Self, PatternError> {
+        let mut parsed = vec![];
+        let mut pos = 0;
+        while pos < bytes.len() {
+            let token = bytes[pos..]
+                .get(..4)
+                .filter(|x| x.starts_with("\\x") && x[2..].bytes().all(|x| x.is_ascii_hexdigit()))
+                .ok_or_else(|| PatternError::InvalidHex {
+                    pos,
+                    token: bytes[pos..].chars().take(4).collect(),
+                })?;
+            parsed.push(u8::from_str_radix(&token[2..], 16).unwrap());
+            pos += 4;
+        }
+        Self::from_code(parsed, mask)
+    }
+    /// build a pattern out of <bytes> and the bits of each byte which have to match, 0 for
+    /// wildcards and 0xFF for exact bytes
+    pub fn from_masked(mut bytes: Vec<u8>, mask: Vec<u8>) -> Result<Self, PatternError> {
+        if bytes.is_empty() {
+            return Err(PatternError::Empty);
+        }
+        if bytes.len() != mask.len() {
+            return Err(PatternError::MaskLength {
+                bytes: bytes.len(),
+                mask: mask.len(),
+            });
+        }
+        bytes
+            .iter_mut()
+            .zip(&mask)
+            .for_each(|(byte, mask)| *byte &= mask);
+        // the rarer the anchor the fewer candidates have to be checked
+        let anchor = (0..bytes.len())
+            .filter(|i| mask[*i] == 0xFF)
+            .min_by_key(|i| commonness(bytes[*i]));
+        Ok(Self {
+            bytes,
+            mask,
+            anchor,
+        })
+    }
+    /// write the pattern IDA style, `48 8B ? 4?`. this is also what [Display] writes.
+    pub fn to_ida(&self) -> String {
+        self.to_spaced("?")
+    }
+    /// write the pattern x64dbg style, `48 8B ?? 4?`
+    pub fn to_x64dbg(&self) -> String {
+        self.to_spaced("??")
+    }
+    /// write the pattern code style as escaped bytes and a mask, `("\x48\x8B\x00", "xx?")`.
+    /// the mask can't express nibbles, so partially known bytes become wildcards.
+    pub fn to_code(&self) -> (String, String) {
+        self.bytes
+            .iter()
+            .zip(&self.mask)
+            .map(|(byte, mask)| match mask {
+                0xFF => (format!("\\x{:02X}", byte), 'x'),
+                _ => ("\\x00".to_string(), '?'),
+            })
+            .unzip()
+    }
+    fn to_spaced(&self, wildcard: &str) -> String {
+        self.bytes
+            .iter()
+            .zip(&self.mask)
+            .map(|(byte, mask)| match (mask >> 4, mask & 0xF) {
+                (0, 0) => wildcard.to_string(),
+                (0, _) => format!("?{:X}", byte & 0xF),
+                (_, 0) => format!("{:X}?", byte >> 4),
+                _ => format!("{:02X}", byte),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+    /// get the amount of bytes the pattern matches
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+    /// always false, patterns can't be empty
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+    /// get the bytes of the pattern, wildcards are 0
+    pub fn get_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+    /// get the bits of each byte which have to match, 0 for wildcards and 0xFF for exact bytes
+    pub fn get_mask(&self) -> &[u8] {
+        &self.mask
+    }
+    /// if the pattern matches the start of <data>
+    pub fn matches(&self, data: &[u8]) -> bool {
+        data.len() >= self.len()
+            && self
+                .bytes
+                .iter()
+                .zip(&self.mask)
+                .zip(data)
+                .all(|((byte, mask), data)| data & mask == *byte)
+    }
+    /// find the offset of the first match in <data>
+    pub fn find(&self, data: &[u8]) -> Option<usize> {
+        self.find_iter(data).next()
+    }
+    /// find the offsets of every match in <data>, including overlapping ones.
+    /// candidates are found by searching for the anchor byte with memchr, which uses SIMD where
+    /// available
+    pub fn find_iter<'a>(&'a self, data: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let starts = (data.len() + 1).saturating_sub(self.len());
+        let candidates: Box<dyn Iterator<Item = usize> + 'a> = match self.anchor {
+            // the anchor of a match starting at i is at i + anchor
+            Some(anchor) if starts > 0 => Box::new(memchr::memchr_iter(
+                self.bytes[anchor],
+                &data[anchor..anchor + starts],
+            )),
+            _ => Box::new(0..starts),
+        };
+        candidates.filter(|i| self.matches(&data[*i..]))
+    }
+    /// find the offset of the first match in a stream of bytes, keeping only the last
+    /// [Pattern::len] bytes around
+    pub fn find_in(&self, data: impl Iterator<Item = u8>) -> Option<usize> {
+        self.find_all_in(data).next()
+    }
+    /// find the offsets of every match in a stream of bytes, keeping only the last
+    /// [Pattern::len] bytes around
+    pub fn find_all_in<I: Iterator<Item = u8>>(&self, data: I) -> StreamMatches<'_, I> {
+        StreamMatches::new(Cow::Borrowed(self), data)
+    }
+}
+
+/// the matches of a [Pattern] in a stream of bytes, see [Pattern::find_all_in]
+#[derive(Debug, Clone)]
+pub struct StreamMatches<'a, I> {
+    pattern: Cow<'a, Pattern>,
+    data: I,
+    /// the last bytes of the stream, as a ring buffer
+    window: Vec<u8>,
+    /// the amount of bytes read from the stream
+    read: usize,
+}
+
+impl<'a, I: Iterator<Item = u8>> StreamMatches<'a, I> {
+    pub(crate) fn new(pattern: Cow<'a, Pattern>, data: I) -> Self {
+        Self {
+            window: vec![0; pattern.len()],
+            pattern,
+            data,
+            read: 0,
+        }
+    }
+}
+
+impl<I: Iterator<Item = u8>> Iterator for StreamMatches<'_, I> {
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        let len = self.pattern.len();
+        for byte in self.data.by_ref() {
+            self.window[self.read % len] = byte;
+            self.read += 1;
+            if self.read < len {
+                continue;
+            }
+            // the oldest byte of the window is the start of the candidate
+            let start = self.read % len;
+            let (bytes, mask) = (&self.pattern.bytes, &self.pattern.mask);
+            if (0..len).all(|j| self.window[(start + j) % len] & mask[j] == bytes[j]) {
+                return Some(self.read - len);
+            }
+        }
+        None
+    }
+}
+
+/// the value and mask of a hex digit or a `?`
+fn nibble(digit: u8) -> (u8, u8) {
+    match digit {
+        b'?' => (0, 0),
+        x => ((x as char).to_digit(16).unwrap() as u8, 0xF),
+    }
+}
+
+/// how often <byte> shows up in code, common bytes make bad anchors
+fn commonness(byte: u8) -> u8 {
+    match byte {
+        0x00 | 0xFF => 4,
+        0xCC | 0x90 | 0x48 => 3,
+        0x8B | 0x89 | 0x0F | 0xE8 | 0x83 => 2,
+        _ => 0,
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = PatternError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_ida())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pattern, PatternError};
+
+    #[test]
+    fn test_parse() {
+        let pattern = Pattern::parse("48 8b ?? 05 ? 9090").unwrap();
+        assert_eq!(pattern.get_bytes(), [0x48, 0x8B, 0, 0x05, 0, 0x90, 0x90]);
+        assert_eq!(pattern.get_mask(), [0xFF, 0xFF, 0, 0xFF, 0, 0xFF, 0xFF]);
+        assert_eq!(pattern.to_string(), "48 8B ? 05 ? 90 90");
+        assert_eq!(pattern.to_string().parse::<Pattern>().unwrap(), pattern);
+
+        assert_eq!(Pattern::parse("  "), Err(PatternError::Empty));
+        assert_eq!(
+            Pattern::parse("48 GG"),
+            Err(PatternError::InvalidHex {
+                pos: 3,
+                token: "GG".into()
+            })
+        );
+        assert_eq!(
+            Pattern::parse("48 8B5"),
+            Err(PatternError::TrailingNibble(5))
+        );
+    }
+
+    #[test]
+    fn test_syntaxes() {
+        let ida = Pattern::parse("48 8B ? 4? ?F").unwrap();
+        assert_eq!(ida.get_bytes(), [0x48, 0x8B, 0, 0x40, 0x0F]);
+        assert_eq!(ida.get_mask(), [0xFF, 0xFF, 0, 0xF0, 0x0F]);
+        assert!(ida.matches(&[0x48, 0x8B, 0x12, 0x4C, 0xAF]));
+        assert!(!ida.matches(&[0x48, 0x8B, 0x12, 0x5C, 0xAF]));
+        assert_eq!(ida.to_ida(), "48 8B ? 4? ?F");
+        assert_eq!(ida.to_x64dbg(), "48 8B ?? 4? ?F");
+        assert_eq!(Pattern::parse(&ida.to_x64dbg()).unwrap(), ida);
+        assert_eq!(Pattern::parse("488B??4??F").unwrap(), ida);
+
+        let code = Pattern::from_code(b"\x48\x8B\x00\x05", "xx?x").unwrap();
+        assert_eq!(code, Pattern::parse("48 8B ?? 05").unwrap());
+        assert_eq!(
+            Pattern::parse_code(r"\x48\x8b\x00\x05", "xx?x").unwrap(),
+            code
+        );
+        let (bytes, mask) = code.to_code();
+        assert_eq!(bytes, r"\x48\x8B\x00\x05");
+        assert_eq!(mask, "xx?x");
+        assert_eq!(Pattern::parse_code(&bytes, &mask).unwrap(), code);
+        // nibbles widen to wildcards
+        assert_eq!(ida.to_code().1, "xx???");
+
+        assert_eq!(
+            Pattern::from_code(b"\x48\x8B", "x"),
+            Err(PatternError::MaskLength { bytes: 2, mask: 1 })
+        );
+        assert_eq!(
+            Pattern::from_code(b"\x48\x8B", "xy"),
+            Err(PatternError::InvalidMask(1, 'y'))
+        );
+        assert_eq!(
+            Pattern::parse_code(r"\x48\x8", "xx"),
+            Err(PatternError::InvalidHex {
+                pos: 4,
+                token: r"\x8".into()
+            })
+        );
+        assert_eq!(Pattern::from_code(b"", ""), Err(PatternError::Empty));
+    }
+
+    #[test]
+    fn test_find() {
+        let pattern = Pattern::parse("AA AB").unwrap();
+        // the old matcher skipped the second AA after the mismatch on it
+        let data = [0xAA, 0xAA, 0xAB];
+        assert_eq!(pattern.find(&data), Some(1));
+        assert_eq!(pattern.find_in(data.iter().copied()), Some(1));
+
+        let pattern = Pattern::parse("01 ? 03").unwrap();
+        let data = [0x01, 0x01, 0x02, 0x03, 0x01];
+        assert_eq!(pattern.find(&data), Some(1));
+        assert_eq!(pattern.find_in(data.iter().copied()), Some(1));
+        assert_eq!(pattern.find(&data[..3]), None);
+        assert_eq!(pattern.find_in(data[..3].iter().copied()), None);
+        assert_eq!(pattern.find(&[]), None);
+
+        // the anchor is the rarest known byte, matches before it still have to be found
+        let pattern = Pattern::parse("00 ? 37 00").unwrap();
+        assert_eq!(pattern.anchor, Some(2));
+        let data = [0x37, 0x00, 0x00, 0x37, 0x00, 0x37];
+        assert_eq!(pattern.find_iter(&data).collect::<Vec<_>>(), [1]);
+        assert_eq!(pattern.find(&data[2..]), None);
+        assert_eq!(Pattern::parse("? ?").unwrap().find(&[1, 2, 3]), Some(0));
+    }
+
+    #[test]
+    fn test_find_all() {
+        let pattern = Pattern::parse("AA ? AA").unwrap();
+        let data = [0xAA, 0x00, 0xAA, 0x01, 0xAA, 0xAA, 0xAA];
+        assert_eq!(pattern.find_iter(&data).collect::<Vec<_>>(), [0, 2, 4]);
+        assert_eq!(
+            pattern
+                .find_all_in(data.iter().copied())
+                .collect::<Vec<_>>(),
+            [0, 2, 4]
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{sigscan::SigScan, traits::MemError};
+use crate::{
+    sigscan::SigScan,
+    traits::{MemError, Pod},
+};
 
 /// represents an address in a process
 pub struct Address<'a, T: SigScan> {
@@ -13,20 +16,71 @@ impl<'a, T: SigScan> Address<'a, T> {
     /// Read the value at the address
     /// # Safety
     /// This function is unsafe because it can read from any address in the process.
-    pub unsafe fn read<V>(&self) -> Result<V, MemError> {
+    pub unsafe fn read<V: Pod>(&self) -> Result<V, MemError> {
         self.owner.read(self.at)
     }
     /// Write the value at the address
     /// # Safety
     /// This function is unsafe because it can write to any address in the process.
-    pub unsafe fn write<V>(&self, value: &V) -> Result<(), MemError> {
-        self.owner.write(self.at, &value)
+    pub unsafe fn write<V: Pod>(&self, value: &V) -> Result<(), MemError> {
+        self.owner.write(self.at, value)
     }
     /// go to an address
     #[inline(always)]
     pub fn goto(&mut self, to: usize) {
         self.at = to;
     }
+    /// get the address being wrapped
+    pub const fn get_addr(&self) -> usize {
+        self.at
+    }
+    /// get the process this address belongs to
+    pub const fn get_owner(&self) -> &'a T {
+        self.owner
+    }
+    /// move the address by a signed <offset>
+    pub fn offset(mut self, offset: isize) -> Self {
+        self.at = self.at.wrapping_add_signed(offset);
+        self
+    }
+    /// read the pointer stored at the address and go to it, sized by
+    /// [pointer_width](crate::traits::Mem::pointer_width)
+    /// # Safety
+    /// This function is unsafe because it can read from any address in the process.
+    pub unsafe fn deref(&self) -> Result<Self, MemError> {
+        Ok(Self::new(self.owner, self.owner.read_ptr(self.at)?))
+    }
+    /// follow a pointer chain starting at the address, like the ones found with cheat engine.
+    /// for each offset the pointer at the current address is read and the offset is added to it,
+    /// so `follow(&[0x10, 0x8])` resolves `[[addr] + 0x10] + 0x8`.
+    /// if a read fails, [MemError::ChainFailure] reports which hop failed and where.
+    /// ```rs
+    /// let health = process.address(base).follow(&[0x10, 0x8])?.read::<f32>()?;
+    /// ```
+    /// # Safety
+    /// This function is unsafe because it can read from any address in the process.
+    pub unsafe fn follow(&self, offsets: &[isize]) -> Result<Self, MemError> {
+        let mut at = self.at;
+        for (hop, offset) in offsets.iter().enumerate() {
+            let ptr = self
+                .owner
+                .read_ptr(at)
+                .map_err(|e| MemError::ChainFailure {
+                    hop,
+                    addr: at,
+                    source: Box::new(e),
+                })?;
+            at = ptr.wrapping_add_signed(*offset);
+        }
+        Ok(Self::new(self.owner, at))
+    }
+}
+impl<'a, T: SigScan> std::fmt::Debug for Address<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Address")
+            .field(&format_args!("{:X}", self.at))
+            .finish()
+    }
 }
 impl<'a, T: SigScan> Clone for Address<'a, T> {
     fn clone(&self) -> Self {
@@ -36,6 +90,16 @@ impl<'a, T: SigScan> Clone for Address<'a, T> {
         }
     }
 }
+impl<'a, T: SigScan> From<Address<'a, T>> for usize {
+    fn from(value: Address<'a, T>) -> Self {
+        value.at
+    }
+}
+impl<'a, T: SigScan> From<&Address<'a, T>> for usize {
+    fn from(value: &Address<'a, T>) -> Self {
+        value.at
+    }
+}
 impl<'a, T: SigScan> std::ops::Add<usize> for Address<'a, T> {
     type Output = Self;
     fn add(mut self, rhs: usize) -> Self::Output {
@@ -64,3 +128,37 @@ impl<'a, T: SigScan> std::ops::Sub<usize> for Address<'a, T> {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Address;
+    use crate::{structures::process::Process, traits::MemError};
+
+    #[test]
+    fn test_follow() {
+        let value = Box::new([0u32, 0x1337]);
+        let inner = Box::new([0usize, &value[0] as *const u32 as usize]);
+        let base = Box::new(&inner[0] as *const usize as usize - 0x10);
+        let proc = Process::find_pid(std::process::id()).unwrap();
+        let addr = Address::new(&proc, &*base as *const usize as usize);
+        unsafe {
+            // [[base] + 0x18] + 0x4
+            let resolved = addr.follow(&[0x18, 0x4]).unwrap();
+            assert_eq!(resolved.read::<u32>().unwrap(), 0x1337);
+            assert_eq!(
+                addr.deref().unwrap().offset(0x18).get_addr(),
+                &inner[1] as *const usize as usize
+            );
+            // inner[0] is null, so the third hop (hops count from 0) reads address 0x4
+            let err = addr.follow(&[0x10, 0x4, 0x0]).unwrap_err();
+            assert!(matches!(
+                err,
+                MemError::ChainFailure {
+                    hop: 2,
+                    addr: 0x4,
+                    ..
+                }
+            ));
+        }
+    }
+}
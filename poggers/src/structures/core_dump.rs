@@ -0,0 +1,596 @@
+use std::{
+    fs::File,
+    os::unix::fs::FileExt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use thiserror::Error;
+
+use crate::{
+    sigscan::SigScan,
+    structures::{
+        modules::{Module, ModuleError},
+        process::implement::utils::ProcessUtils,
+        protections::Protections,
+        region::MemoryRegion,
+    },
+    traits::{Mem, MemError, PointerWidth, SafeMem},
+};
+
+const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+const ELFCLASS32: u8 = 1;
+const ELFCLASS64: u8 = 2;
+const ELFDATA2LSB: u8 = 1;
+const ET_CORE: u16 = 4;
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const PF_X: u32 = 1;
+const PF_W: u32 = 2;
+const PF_R: u32 = 4;
+const NT_PRPSINFO: u32 = 3;
+/// "FILE", the mapped files of the process
+const NT_FILE: u32 = 0x4649_4c45;
+/// offset of pr_pid and pr_fname in the 64-bit elf_prpsinfo
+const PRPSINFO_PID: usize = 24;
+const PRPSINFO_FNAME: usize = 40;
+
+/// a process restored from an ELF core file, such as one written by gcore or the kernel.
+/// memory is backed by the PT_LOAD segments and modules by the NT_FILE note.
+/// the memory is read only, writing, allocating and changing protections fail with
+/// [MemError::Unsupported].
+/// ```rs
+/// let core = CoreDump::open("game.core")?;
+/// let module = core.get_module("libgame.so")?;
+/// let health = module.scan("F3 0F 10 ? ? ? ? ? 0F 2F")?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct CoreDump {
+    pub(crate) source: Arc<Source>,
+    pub(crate) segments: Arc<[Segment]>,
+    pub(crate) pid: u32,
+    pub(crate) name: String,
+}
+
+#[derive(Debug)]
+pub(crate) enum Source {
+    File(File),
+    Bytes(Vec<u8>),
+}
+
+#[derive(Debug)]
+pub(crate) struct Segment {
+    region: MemoryRegion,
+    /// offset of the contents in the core file
+    file_offset: u64,
+    /// amount of bytes of the segment stored in the core file, the rest was not dumped
+    file_size: usize,
+}
+
+/// errors from opening a core file
+#[derive(Debug, Error)]
+pub enum CoreDumpError {
+    /// the file could not be read
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    /// the file is not an ELF core file
+    #[error("not an ELF core file")]
+    NotCore,
+    /// the core file is not 64-bit, the ELF class is the field
+    #[error("unsupported ELF class {0}, only 64-bit core files are supported")]
+    UnsupportedClass(u8),
+    /// the core file is not little endian, the ELF byte order is the field
+    #[error("unsupported ELF byte order {0}, only little endian core files are supported")]
+    UnsupportedByteOrder(u8),
+    /// a header or note points outside of the file
+    #[error("malformed core file: {0}")]
+    Malformed(&'static str),
+}
+
+/// a mapped file from the NT_FILE note
+struct FileMapping {
+    start: usize,
+    end: usize,
+    offset: usize,
+    path: Arc<Path>,
+}
+
+impl Source {
+    fn len(&self) -> std::io::Result<u64> {
+        match self {
+            Source::File(file) => Ok(file.metadata()?.len()),
+            Source::Bytes(bytes) => Ok(bytes.len() as u64),
+        }
+    }
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> std::io::Result<()> {
+        match self {
+            Source::File(file) => file.read_exact_at(buf, offset),
+            Source::Bytes(bytes) => {
+                let data = usize::try_from(offset)
+                    .ok()
+                    .and_then(|start| bytes.get(start..start.checked_add(buf.len())?))
+                    .ok_or(std::io::ErrorKind::UnexpectedEof)?;
+                buf.copy_from_slice(data);
+                Ok(())
+            }
+        }
+    }
+    /// read <size> bytes at <offset>, checking they are in the file before allocating
+    fn read_vec(&self, offset: u64, size: usize) -> Result<Vec<u8>, CoreDumpError> {
+        let in_file = offset
+            .checked_add(size as u64)
+            .is_some_and(|end| end <= self.len().unwrap_or(u64::MAX));
+        if !in_file {
+            return Err(CoreDumpError::Malformed("truncated file"));
+        }
+        let mut buf = vec![0; size];
+        self.read_at(&mut buf, offset).map_err(|e| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => CoreDumpError::Malformed("truncated file"),
+            _ => CoreDumpError::Io(e),
+        })?;
+        Ok(buf)
+    }
+}
+
+fn u16_at(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap())
+}
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+fn u64_at(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+impl CoreDump {
+    /// open the core file at <path>, segments are read from the file on demand
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CoreDumpError> {
+        Self::parse(Source::File(File::open(path)?))
+    }
+    /// parse a core file which is already in memory
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, CoreDumpError> {
+        Self::parse(Source::Bytes(data))
+    }
+    /// get the pid of the dumped process, 0 if the core file has no NT_PRPSINFO note
+    pub const fn get_pid(&self) -> u32 {
+        self.pid
+    }
+    fn parse(source: Source) -> Result<Self, CoreDumpError> {
+        let header = source.read_vec(0, 64).map_err(|_| CoreDumpError::NotCore)?;
+        if &header[..4] != ELF_MAGIC {
+            return Err(CoreDumpError::NotCore);
+        }
+        match header[4] {
+            ELFCLASS64 => {}
+            ELFCLASS32 => return Err(CoreDumpError::UnsupportedClass(ELFCLASS32)),
+            _ => return Err(CoreDumpError::NotCore),
+        }
+        if header[5] != ELFDATA2LSB {
+            return Err(CoreDumpError::UnsupportedByteOrder(header[5]));
+        }
+        if u16_at(&header, 16) != ET_CORE {
+            return Err(CoreDumpError::NotCore);
+        }
+        let phoff = u64_at(&header, 32);
+        let phentsize = u16_at(&header, 54) as usize;
+        let phnum = u16_at(&header, 56) as usize;
+        if phentsize < 56 {
+            return Err(CoreDumpError::Malformed("program header size"));
+        }
+        let headers_size = phentsize
+            .checked_mul(phnum)
+            .ok_or(CoreDumpError::Malformed("program header size"))?;
+        let headers = source.read_vec(phoff, headers_size)?;
+
+        let mut loads = vec![];
+        let mut files = vec![];
+        let (mut pid, mut name) = (0, String::new());
+        for header in headers.chunks_exact(phentsize) {
+            let offset = u64_at(header, 8);
+            let file_size = u64_at(header, 32) as usize;
+            match u32_at(header, 0) {
+                PT_LOAD => loads.push((
+                    u32_at(header, 4),
+                    offset,
+                    u64_at(header, 16) as usize,
+                    file_size,
+                    u64_at(header, 40) as usize,
+                )),
+                PT_NOTE => {
+                    let notes = source.read_vec(offset, file_size)?;
+                    for (kind, desc) in parse_notes(&notes)? {
+                        match kind {
+                            NT_FILE => files = parse_file_note(desc)?,
+                            NT_PRPSINFO if desc.len() >= PRPSINFO_FNAME + 16 => {
+                                pid = u32_at(desc, PRPSINFO_PID);
+                                let fname = &desc[PRPSINFO_FNAME..PRPSINFO_FNAME + 16];
+                                let len = fname.iter().position(|x| *x == 0).unwrap_or(16);
+                                name = String::from_utf8_lossy(&fname[..len]).into_owned();
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let file_len = source.len()?;
+        let mut segments = loads
+            .into_iter()
+            .filter(|(_, _, _, _, size)| *size > 0)
+            .map(|(flags, file_offset, start, file_size, size)| {
+                let malformed = || CoreDumpError::Malformed("segment bounds");
+                let end = start.checked_add(size).ok_or_else(malformed)?;
+                file_offset
+                    .checked_add(file_size as u64)
+                    .ok_or_else(malformed)?;
+                // a truncated core can only be read up to the end of the file
+                let in_file =
+                    usize::try_from(file_len.saturating_sub(file_offset)).unwrap_or(usize::MAX);
+                let mapping = files.iter().find(|x| x.start <= start && start < x.end);
+                let offset = match mapping {
+                    Some(x) => x
+                        .offset
+                        .checked_add(start - x.start)
+                        .ok_or_else(malformed)?,
+                    None => 0,
+                };
+                Ok(Segment {
+                    region: MemoryRegion {
+                        start,
+                        end,
+                        protections: Protections::new()
+                            .with_read(flags & PF_R != 0)
+                            .with_write(flags & PF_W != 0)
+                            .with_execute(flags & PF_X != 0),
+                        shared: false,
+                        offset,
+                        path: mapping.map(|x| x.path.clone()),
+                    },
+                    file_offset,
+                    file_size: file_size.min(size).min(in_file),
+                })
+            })
+            .collect::<Result<Vec<_>, CoreDumpError>>()?;
+        segments.sort_by_key(|x| x.region.start);
+        Ok(Self {
+            source: Arc::new(source),
+            segments: segments.into(),
+            pid,
+            name,
+        })
+    }
+    fn segment_at(&self, addr: usize) -> Option<&Segment> {
+        let idx = self.segments.partition_point(|x| x.region.end <= addr);
+        self.segments.get(idx).filter(|x| x.region.start <= addr)
+    }
+}
+
+/// splits a PT_NOTE segment into (type, descriptor) pairs
+fn parse_notes(mut data: &[u8]) -> Result<Vec<(u32, &[u8])>, CoreDumpError> {
+    let align = |x: usize| x.checked_next_multiple_of(4);
+    let mut notes = vec![];
+    while data.len() >= 12 {
+        let name_size = u32_at(data, 0) as usize;
+        let desc_size = u32_at(data, 4) as usize;
+        let kind = u32_at(data, 8);
+        let malformed = || CoreDumpError::Malformed("note size");
+        let desc_start = align(name_size)
+            .and_then(|x| x.checked_add(12))
+            .ok_or_else(malformed)?;
+        let desc_end = desc_start.checked_add(desc_size).ok_or_else(malformed)?;
+        let desc = data.get(desc_start..desc_end).ok_or_else(malformed)?;
+        notes.push((kind, desc));
+        data = align(desc_size)
+            .and_then(|x| desc_start.checked_add(x))
+            .and_then(|next| data.get(next..))
+            .unwrap_or_default();
+    }
+    Ok(notes)
+}
+
+/// parses the NT_FILE note, a list of (start, end, page offset) followed by the paths
+fn parse_file_note(desc: &[u8]) -> Result<Vec<FileMapping>, CoreDumpError> {
+    let malformed = || CoreDumpError::Malformed("NT_FILE note");
+    if desc.len() < 16 {
+        return Err(malformed());
+    }
+    let count = u64_at(desc, 0) as usize;
+    let page_size = u64_at(desc, 8) as usize;
+    let names_start = count
+        .checked_mul(24)
+        .and_then(|x| x.checked_add(16))
+        .filter(|x| *x <= desc.len())
+        .ok_or_else(malformed)?;
+    let mut names = desc[names_start..].split(|x| *x == 0);
+    (0..count)
+        .map(|i| {
+            let entry = 16 + i * 24;
+            let name = names.next().ok_or_else(malformed)?;
+            Ok(FileMapping {
+                start: u64_at(desc, entry) as usize,
+                end: u64_at(desc, entry + 8) as usize,
+                offset: (u64_at(desc, entry + 16) as usize)
+                    .checked_mul(page_size)
+                    .ok_or_else(malformed)?,
+                path: Arc::from(PathBuf::from(String::from_utf8_lossy(name).as_ref())),
+            })
+        })
+        .collect()
+}
+
+impl Mem for CoreDump {
+    /// 32-bit core files are rejected with [CoreDumpError::UnsupportedClass] when opened
+    fn pointer_width(&self) -> PointerWidth {
+        PointerWidth::Bits64
+    }
+    fn regions(&self) -> Result<impl Iterator<Item = MemoryRegion>, MemError> {
+        Ok(self
+            .segments
+            .iter()
+            .map(|x| x.region.clone())
+            .collect::<Vec<_>>()
+            .into_iter())
+    }
+    unsafe fn alter_protection(
+        &self,
+        _addr: usize,
+        _size: usize,
+        _prot: Protections,
+    ) -> Result<Protections, MemError> {
+        Err(MemError::Unsupported)
+    }
+    /// reads the dumped contents, memory which is mapped but was not written to the core file
+    /// (such as file backed code the kernel skips) can't be read
+    unsafe fn raw_read(&self, addr: usize, data: *mut u8, size: usize) -> Result<(), MemError> {
+        let out = std::slice::from_raw_parts_mut(data, size);
+        let mut done = 0;
+        while done < size {
+            let at = addr + done;
+            let Some(segment) = self.segment_at(at) else {
+                break;
+            };
+            let offset = at - segment.region.start;
+            if offset >= segment.file_size {
+                break;
+            }
+            let len = (size - done).min(segment.file_size - offset);
+            let Some(file_offset) = segment.file_offset.checked_add(offset as u64) else {
+                break;
+            };
+            if self
+                .source
+                .read_at(&mut out[done..done + len], file_offset)
+                .is_err()
+            {
+                break;
+            }
+            done += len;
+        }
+        match done {
+            x if x == size => Ok(()),
+            0 => Err(MemError::ReadFailure(addr)),
+            transferred => Err(MemError::PartialRead {
+                addr,
+                transferred,
+                size,
+            }),
+        }
+    }
+    unsafe fn raw_write(
+        &self,
+        _addr: usize,
+        _data: *const u8,
+        _size: usize,
+    ) -> Result<(), MemError> {
+        Err(MemError::Unsupported)
+    }
+    unsafe fn raw_virtual_alloc(
+        &self,
+        _addr: Option<usize>,
+        _size: usize,
+        _prot: Protections,
+    ) -> Result<usize, MemError> {
+        Err(MemError::Unsupported)
+    }
+    unsafe fn raw_virtual_free(&self, _addr: usize, _size: usize) -> Result<(), MemError> {
+        Err(MemError::Unsupported)
+    }
+}
+
+// memory is copied out of the core file
+unsafe impl SafeMem for CoreDump {}
+
+impl SigScan for CoreDump {}
+
+impl ProcessUtils for CoreDump {
+    fn get_module(&self, name: &str) -> Result<Module<Self>, ModuleError>
+    where
+        Self: Sized + SigScan,
+    {
+        let regions = self.segments.iter().map(|x| x.region.clone());
+        crate::structures::process::implement::module_from_regions(
+            Arc::new(self.clone()),
+            name,
+            regions,
+        )
+    }
+    /// the name from the NT_PRPSINFO note, which the kernel truncates to 15 bytes
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoreDump, CoreDumpError, NT_FILE, NT_PRPSINFO, PT_LOAD, PT_NOTE};
+    use crate::{
+        structures::{expr::Expression, process::implement::utils::ProcessUtils},
+        traits::{Mem, MemError},
+    };
+
+    fn note(kind: u32, desc: &[u8]) -> Vec<u8> {
+        let mut out = vec![];
+        out.extend(5u32.to_le_bytes());
+        out.extend((desc.len() as u32).to_le_bytes());
+        out.extend(kind.to_le_bytes());
+        out.extend(b"CORE\0\0\0\0");
+        out.extend(desc);
+        out.resize(out.len().next_multiple_of(4), 0);
+        out
+    }
+
+    /// a core with a module mapped at 0x400000 (code not dumped, data dumped) and a heap
+    fn build_core() -> Vec<u8> {
+        let mut prpsinfo = vec![0u8; 136];
+        prpsinfo[24..28].copy_from_slice(&4242u32.to_le_bytes());
+        prpsinfo[40..44].copy_from_slice(b"game");
+        let mut files = vec![];
+        files.extend(2u64.to_le_bytes());
+        files.extend(0x1000u64.to_le_bytes());
+        for (start, end, page) in [(0x400000u64, 0x401000u64, 0u64), (0x401000, 0x402000, 1)] {
+            files.extend(start.to_le_bytes());
+            files.extend(end.to_le_bytes());
+            files.extend(page.to_le_bytes());
+        }
+        files.extend(b"/opt/game/libgame.so\0/opt/game/libgame.so\0");
+        let notes = [note(NT_PRPSINFO, &prpsinfo), note(NT_FILE, &files)].concat();
+
+        let mut data = vec![0u8; 0x1000];
+        data[8..16].copy_from_slice(&0x10000u64.to_le_bytes());
+        let mut heap = vec![0u8; 0x2000];
+        heap[0x10..0x14].copy_from_slice(&0x1337u32.to_le_bytes());
+        heap[0x100..0x105].copy_from_slice(&[0x48, 0x8B, 0x05, 0x10, 0x20]);
+
+        // (type, flags, vaddr, data, memsz)
+        let segments: [(u32, u32, u64, &[u8], u64); 4] = [
+            (PT_NOTE, 0, 0, &notes, 0),
+            (PT_LOAD, 5, 0x400000, &[], 0x1000),
+            (PT_LOAD, 6, 0x401000, &data, 0x1000),
+            (PT_LOAD, 6, 0x10000, &heap, 0x2000),
+        ];
+        let mut out = vec![0u8; 64];
+        out[..4].copy_from_slice(b"\x7fELF");
+        out[4] = 2;
+        out[5] = 1;
+        out[16..18].copy_from_slice(&4u16.to_le_bytes());
+        out[32..40].copy_from_slice(&64u64.to_le_bytes());
+        out[54..56].copy_from_slice(&56u16.to_le_bytes());
+        out[56..58].copy_from_slice(&(segments.len() as u16).to_le_bytes());
+        let mut offset = 64 + 56 * segments.len() as u64;
+        for (kind, flags, vaddr, contents, memsz) in segments {
+            let mut header = vec![0u8; 56];
+            header[0..4].copy_from_slice(&kind.to_le_bytes());
+            header[4..8].copy_from_slice(&flags.to_le_bytes());
+            header[8..16].copy_from_slice(&offset.to_le_bytes());
+            header[16..24].copy_from_slice(&vaddr.to_le_bytes());
+            header[32..40].copy_from_slice(&(contents.len() as u64).to_le_bytes());
+            header[40..48].copy_from_slice(&memsz.to_le_bytes());
+            out.extend(header);
+            offset += contents.len() as u64;
+        }
+        for (_, _, _, contents, _) in segments {
+            out.extend(contents);
+        }
+        out
+    }
+
+    #[test]
+    fn test_core_dump() {
+        let core = CoreDump::from_bytes(build_core()).unwrap();
+        assert_eq!(core.get_name(), "game");
+        assert_eq!(core.get_pid(), 4242);
+        assert_eq!(core.regions().unwrap().count(), 3);
+
+        let module = core.get_module("libgame.so").unwrap();
+        assert_eq!(module.get_base_address(), 0x400000);
+        assert_eq!(module.get_size(), 0x2000);
+        let data = core.query(0x401008).unwrap();
+        assert_eq!(data.get_offset(), 0x1000);
+        assert!(data.is_writable() && !data.is_executable());
+
+        unsafe {
+            assert_eq!(core.read::<u32>(0x10010).unwrap(), 0x1337);
+            assert!(matches!(
+                core.read::<u32>(0x400000),
+                Err(MemError::ReadFailure(0x400000))
+            ));
+            assert!(matches!(
+                core.read_sized(0x11FF0, 0x20),
+                Err(MemError::PartialRead {
+                    transferred: 0x10,
+                    ..
+                })
+            ));
+            assert!(matches!(
+                core.write(0x10010, &1u32),
+                Err(MemError::Unsupported)
+            ));
+            let addr = Expression::parse("libgame.so + 0x1008 -> 0x10")
+                .unwrap()
+                .resolve(&core)
+                .unwrap();
+            assert_eq!(addr.get_addr(), 0x10010);
+        }
+
+        let path = std::env::temp_dir().join(format!("poggers-core-{}", std::process::id()));
+        std::fs::write(&path, build_core()).unwrap();
+        let file = CoreDump::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(unsafe { file.read::<u32>(0x10010).unwrap() }, 0x1337);
+
+        assert!(matches!(
+            CoreDump::from_bytes(b"not a core".to_vec()),
+            Err(CoreDumpError::NotCore)
+        ));
+    }
+
+    #[test]
+    fn test_untrusted_core() {
+        let patched = |at: usize, bytes: &[u8]| {
+            let mut core = build_core();
+            core[at..at + bytes.len()].copy_from_slice(bytes);
+            CoreDump::from_bytes(core)
+        };
+        assert!(matches!(
+            patched(4, &[1]),
+            Err(CoreDumpError::UnsupportedClass(1))
+        ));
+        assert!(matches!(
+            patched(5, &[2]),
+            Err(CoreDumpError::UnsupportedByteOrder(2))
+        ));
+        // the program headers don't fit in the file
+        assert!(matches!(
+            patched(56, &u16::MAX.to_le_bytes()),
+            Err(CoreDumpError::Malformed(_))
+        ));
+        // the notes claim to be larger than the file
+        assert!(matches!(
+            patched(64 + 32, &u64::MAX.to_le_bytes()),
+            Err(CoreDumpError::Malformed(_))
+        ));
+        // a load which wraps around the address space
+        let load = 64 + 56 * 3;
+        assert!(matches!(
+            patched(load + 16, &u64::MAX.to_le_bytes()),
+            Err(CoreDumpError::Malformed(_))
+        ));
+        // a load whose contents wrap around the end of the file
+        assert!(matches!(
+            patched(load + 8, &(u64::MAX - 8).to_le_bytes()),
+            Err(CoreDumpError::Malformed(_))
+        ));
+        assert!(matches!(
+            patched(load + 32, &u64::MAX.to_le_bytes()),
+            Err(CoreDumpError::Malformed(_))
+        ));
+        let mut core = build_core();
+        core.truncate(core.len() - 1);
+        assert!(matches!(
+            CoreDump::from_bytes(core),
+            Ok(core) if unsafe { core.read::<u8>(0x11FFF).is_err() }
+        ));
+    }
+}
@@ -0,0 +1,464 @@
+use std::str::FromStr;
+
+use crate::{
+    sigscan::SigScan,
+    structures::{addr::Address, modules::ModuleError, process::implement::utils::ProcessUtils},
+    traits::MemError,
+};
+
+/// an address expression, in the style of cheat engine.
+/// supports module names (`client.dll`, or quoted `"libgame.so"` for names with other
+/// characters), hex (`0x1A2B`) and decimal literals, `+ - * /`, bracketed dereferences
+/// (`[client.dll+0x100]`) and pointer chain arrows where `a -> 0x18` means `[a] + 0x18`.
+/// ```rs
+/// let expr: Expression = r#""libgame.so"+0x1A2B30 -> 0x18 -> 0x4"#.parse()?;
+/// let health = expr.resolve(&process)?.read::<f32>()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Number(usize),
+    Module {
+        name: String,
+        pos: usize,
+    },
+    Deref {
+        inner: Box<Node>,
+        pos: usize,
+    },
+    Neg(Box<Node>),
+    Binary {
+        op: Op,
+        lhs: Box<Node>,
+        rhs: Box<Node>,
+        pos: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(usize),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Arrow,
+    OpenBracket,
+    CloseBracket,
+    OpenParen,
+    CloseParen,
+    End,
+}
+
+/// Expression failures, positions are byte offsets into the expression
+#[derive(Debug, thiserror::Error)]
+pub enum ExprError {
+    /// a token which doesn't fit the grammar at this position
+    #[error("unexpected '{token}' at {pos}")]
+    UnexpectedToken {
+        /// position of the token
+        pos: usize,
+        /// the token
+        token: String,
+    },
+    /// the expression ended early
+    #[error("unexpected end of expression at {0}")]
+    UnexpectedEnd(usize),
+    /// a number literal which doesn't parse
+    #[error("invalid number '{token}' at {pos}")]
+    InvalidNumber {
+        /// position of the literal
+        pos: usize,
+        /// the literal
+        token: String,
+    },
+    /// a quoted module name which is never closed
+    #[error("unterminated string starting at {0}")]
+    UnterminatedString(usize),
+    /// a module in the expression could not be found in the process
+    #[error("unable to find module '{name}' at {pos}: {source}")]
+    UnknownModule {
+        /// position of the module name
+        pos: usize,
+        /// the module name
+        name: String,
+        /// the error from looking up the module
+        source: ModuleError,
+    },
+    /// a dereference failed to read the pointer
+    #[error("unable to dereference [{addr:X}] at {pos}: {source}")]
+    ReadFailure {
+        /// position of the `[` or `->` which failed
+        pos: usize,
+        /// the address which could not be read
+        addr: usize,
+        /// the error from reading the pointer
+        source: MemError,
+    },
+    /// division by zero
+    #[error("division by zero at {0}")]
+    DivisionByZero(usize),
+}
+
+impl Expression {
+    /// parse an expression
+    pub fn parse(source: &str) -> Result<Self, ExprError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, at: 0 };
+        let root = parser.chain()?;
+        let (pos, token) = parser.peek();
+        if *token != Token::End {
+            return Err(parser.error(pos, token));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            root,
+        })
+    }
+    /// get the source of the expression
+    pub fn get_source(&self) -> &str {
+        &self.source
+    }
+    /// resolve the expression against a process, looking up modules and reading pointers
+    /// # Safety
+    /// dereferences read from whatever addresses the expression produces.
+    pub unsafe fn resolve<'a, T>(&self, process: &'a T) -> Result<Address<'a, T>, ExprError>
+    where
+        T: SigScan + ProcessUtils,
+    {
+        Ok(Address::new(process, eval(&self.root, process)?))
+    }
+}
+
+impl FromStr for Expression {
+    type Err = ExprError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+unsafe fn eval<T: SigScan + ProcessUtils>(node: &Node, process: &T) -> Result<usize, ExprError> {
+    Ok(match node {
+        Node::Number(x) => *x,
+        Node::Module { name, pos } => process
+            .get_module(name)
+            .map_err(|source| ExprError::UnknownModule {
+                pos: *pos,
+                name: name.clone(),
+                source,
+            })?
+            .get_base_address(),
+        Node::Deref { inner, pos } => {
+            let addr = eval(inner, process)?;
+            process
+                .read_ptr(addr)
+                .map_err(|source| ExprError::ReadFailure {
+                    pos: *pos,
+                    addr,
+                    source,
+                })?
+        }
+        Node::Neg(inner) => eval(inner, process)?.wrapping_neg(),
+        Node::Binary { op, lhs, rhs, pos } => {
+            let (lhs, rhs) = (eval(lhs, process)?, eval(rhs, process)?);
+            match op {
+                Op::Add => lhs.wrapping_add(rhs),
+                Op::Sub => lhs.wrapping_sub(rhs),
+                Op::Mul => lhs.wrapping_mul(rhs),
+                Op::Div => lhs
+                    .checked_div(rhs)
+                    .ok_or(ExprError::DivisionByZero(*pos))?,
+            }
+        }
+    })
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ExprError> {
+    let mut tokens = vec![];
+    let bytes = source.as_bytes();
+    let mut at = 0;
+    while at < bytes.len() {
+        let pos = at;
+        let token = match bytes[at] {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                at += 1;
+                continue;
+            }
+            b'-' if bytes.get(at + 1) == Some(&b'>') => {
+                at += 1;
+                Token::Arrow
+            }
+            b'+' => Token::Plus,
+            b'-' => Token::Minus,
+            b'*' => Token::Star,
+            b'/' => Token::Slash,
+            b'[' => Token::OpenBracket,
+            b']' => Token::CloseBracket,
+            b'(' => Token::OpenParen,
+            b')' => Token::CloseParen,
+            b'"' => {
+                let len = source[at + 1..]
+                    .find('"')
+                    .ok_or(ExprError::UnterminatedString(pos))?;
+                at += len + 1;
+                Token::Ident(source[pos + 1..at].to_string())
+            }
+            b'0'..=b'9' => {
+                while bytes.get(at + 1).is_some_and(u8::is_ascii_alphanumeric) {
+                    at += 1;
+                }
+                let literal = &source[pos..=at];
+                let number = match literal
+                    .strip_prefix("0x")
+                    .or_else(|| literal.strip_prefix("0X"))
+                {
+                    Some(hex) => usize::from_str_radix(hex, 16),
+                    None => literal.parse(),
+                };
+                Token::Number(number.map_err(|_| ExprError::InvalidNumber {
+                    pos,
+                    token: literal.to_string(),
+                })?)
+            }
+            x if x.is_ascii_alphabetic() || x == b'_' => {
+                while bytes
+                    .get(at + 1)
+                    .is_some_and(|x| x.is_ascii_alphanumeric() || matches!(x, b'_' | b'.'))
+                {
+                    at += 1;
+                }
+                Token::Ident(source[pos..=at].to_string())
+            }
+            _ => {
+                return Err(ExprError::UnexpectedToken {
+                    pos,
+                    token: source[pos..].chars().next().unwrap_or_default().to_string(),
+                })
+            }
+        };
+        at += 1;
+        tokens.push((pos, token));
+    }
+    tokens.push((source.len(), Token::End));
+    Ok(tokens)
+}
+
+/// recursive descent parser, lowest to highest precedence:
+/// `chain := sum ('->' sum)*`, `sum := product (('+'|'-') product)*`,
+/// `product := unary (('*'|'/') unary)*`, `unary := '-' unary | primary`,
+/// `primary := number | module | '[' chain ']' | '(' chain ')'`
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    at: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> (usize, &Token) {
+        let (pos, token) = &self.tokens[self.at];
+        (*pos, token)
+    }
+    fn next(&mut self) -> (usize, Token) {
+        let next = self.tokens[self.at].clone();
+        if next.1 != Token::End {
+            self.at += 1;
+        }
+        next
+    }
+    fn chain(&mut self) -> Result<Node, ExprError> {
+        let mut node = self.sum()?;
+        while let (pos, Token::Arrow) = self.peek() {
+            self.next();
+            node = Node::Binary {
+                op: Op::Add,
+                lhs: Box::new(Node::Deref {
+                    inner: Box::new(node),
+                    pos,
+                }),
+                rhs: Box::new(self.sum()?),
+                pos,
+            };
+        }
+        Ok(node)
+    }
+    fn sum(&mut self) -> Result<Node, ExprError> {
+        let mut node = self.product()?;
+        loop {
+            let (pos, op) = match self.peek() {
+                (pos, Token::Plus) => (pos, Op::Add),
+                (pos, Token::Minus) => (pos, Op::Sub),
+                _ => return Ok(node),
+            };
+            self.next();
+            node = Node::Binary {
+                op,
+                lhs: Box::new(node),
+                rhs: Box::new(self.product()?),
+                pos,
+            };
+        }
+    }
+    fn product(&mut self) -> Result<Node, ExprError> {
+        let mut node = self.unary()?;
+        loop {
+            let (pos, op) = match self.peek() {
+                (pos, Token::Star) => (pos, Op::Mul),
+                (pos, Token::Slash) => (pos, Op::Div),
+                _ => return Ok(node),
+            };
+            self.next();
+            node = Node::Binary {
+                op,
+                lhs: Box::new(node),
+                rhs: Box::new(self.unary()?),
+                pos,
+            };
+        }
+    }
+    fn unary(&mut self) -> Result<Node, ExprError> {
+        if let (_, Token::Minus) = self.peek() {
+            self.next();
+            return Ok(Node::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+    fn primary(&mut self) -> Result<Node, ExprError> {
+        match self.next() {
+            (_, Token::Number(x)) => Ok(Node::Number(x)),
+            (pos, Token::Ident(name)) => Ok(Node::Module { name, pos }),
+            (pos, Token::OpenBracket) => {
+                let inner = self.chain()?;
+                self.expect(Token::CloseBracket)?;
+                Ok(Node::Deref {
+                    inner: Box::new(inner),
+                    pos,
+                })
+            }
+            (_, Token::OpenParen) => {
+                let inner = self.chain()?;
+                self.expect(Token::CloseParen)?;
+                Ok(inner)
+            }
+            (pos, token) => Err(self.error(pos, &token)),
+        }
+    }
+    fn expect(&mut self, expected: Token) -> Result<(), ExprError> {
+        match self.next() {
+            (_, token) if token == expected => Ok(()),
+            (pos, token) => Err(self.error(pos, &token)),
+        }
+    }
+    fn error(&self, pos: usize, token: &Token) -> ExprError {
+        if *token == Token::End {
+            return ExprError::UnexpectedEnd(pos);
+        }
+        ExprError::UnexpectedToken {
+            pos,
+            token: token_text(token),
+        }
+    }
+}
+
+fn token_text(token: &Token) -> String {
+    match token {
+        Token::Number(x) => format!("{:#X}", x),
+        Token::Ident(x) => x.clone(),
+        Token::Plus => "+".into(),
+        Token::Minus => "-".into(),
+        Token::Star => "*".into(),
+        Token::Slash => "/".into(),
+        Token::Arrow => "->".into(),
+        Token::OpenBracket => "[".into(),
+        Token::CloseBracket => "]".into(),
+        Token::OpenParen => "(".into(),
+        Token::CloseParen => ")".into(),
+        Token::End => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExprError, Expression};
+    use crate::structures::process::{implement::utils::ProcessUtils, Process};
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            Expression::parse("[client.dll+0x100"),
+            Err(ExprError::UnexpectedEnd(17))
+        ));
+        assert!(matches!(
+            Expression::parse("0x10 + ]"),
+            Err(ExprError::UnexpectedToken { pos: 7, .. })
+        ));
+        assert!(matches!(
+            Expression::parse("0x1G"),
+            Err(ExprError::InvalidNumber { pos: 0, .. })
+        ));
+        assert!(matches!(
+            Expression::parse("\"libgame.so"),
+            Err(ExprError::UnterminatedString(0))
+        ));
+        assert!(matches!(
+            Expression::parse("0x10 0x20"),
+            Err(ExprError::UnexpectedToken { pos: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let proc = Process::find_pid(std::process::id()).unwrap();
+        let exe = std::env::current_exe().unwrap();
+        let exe = exe.file_name().unwrap().to_str().unwrap();
+        let base = proc.get_module(exe).unwrap().get_base_address();
+
+        let value = Box::new([0u32, 0x1337]);
+        let ptr = Box::new(&value[0] as *const u32 as usize);
+        let ptr_addr = &*ptr as *const usize as usize;
+        unsafe {
+            let resolve = |expr: &str| Expression::parse(expr).unwrap().resolve(&proc);
+
+            assert_eq!(resolve("2 * (3 + 0x4) - 1").unwrap().get_addr(), 13);
+            assert_eq!(
+                resolve(&format!("\"{}\" + 0x10", exe)).unwrap().get_addr(),
+                base + 0x10
+            );
+            let chain = resolve(&format!("{:#X} -> 4", ptr_addr)).unwrap();
+            assert_eq!(chain.read::<u32>().unwrap(), 0x1337);
+            let bracketed = resolve(&format!("[{}] + 4", ptr_addr)).unwrap();
+            assert_eq!(bracketed.get_addr(), chain.get_addr());
+
+            assert!(matches!(
+                resolve("missing.so + 0x10"),
+                Err(ExprError::UnknownModule { pos: 0, .. })
+            ));
+            assert!(matches!(
+                resolve("0x10 -> 0x8"),
+                Err(ExprError::ReadFailure {
+                    pos: 5,
+                    addr: 0x10,
+                    ..
+                })
+            ));
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    collections::HashMap,
+    sync::mpsc::{Receiver, SyncSender},
+    time::{Duration, Instant},
+};
+
+use super::poller::{Poll, Poller};
+use crate::traits::{Mem, MemError, Pod};
+
+/// the amount of failures kept until they are received, later failures are dropped
+const MAX_PENDING_FAILURES: usize = 256;
+
+/// keeps values pinned by rewriting them from a background thread.
+/// the thread stops when the freezer is dropped.
+/// ```rs
+/// let freezer = Freezer::new(process.clone())?;
+/// let health = unsafe { freezer.freeze(health_addr, 100f32, Duration::from_millis(10)) };
+/// freezer.pause(health);
+/// for failure in freezer.failures() { .. }
+/// ```
+#[derive(Debug)]
+pub struct Freezer {
+    poller: Poller<Command>,
+    failures: Receiver<FreezeFailure>,
+}
+
+/// identifies a frozen value of a [Freezer]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FreezeId(u64);
+
+/// a failed write of a frozen value
+#[derive(Debug)]
+pub struct FreezeFailure {
+    pub(crate) id: FreezeId,
+    pub(crate) addr: usize,
+    pub(crate) error: MemError,
+}
+
+impl FreezeFailure {
+    /// get the frozen value which failed to be written
+    pub const fn get_id(&self) -> FreezeId {
+        self.id
+    }
+    /// get the address which failed to be written
+    pub const fn get_addr(&self) -> usize {
+        self.addr
+    }
+    /// get the error of the write
+    pub const fn get_error(&self) -> &MemError {
+        &self.error
+    }
+}
+
+#[derive(Debug)]
+enum Command {
+    Freeze(FreezeId, Entry),
+    SetPaused(FreezeId, bool),
+    Remove(FreezeId),
+}
+
+/// the state of the freezer thread
+struct Frozen<T> {
+    process: T,
+    entries: HashMap<FreezeId, Entry>,
+    failures: SyncSender<FreezeFailure>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    addr: usize,
+    data: Vec<u8>,
+    interval: Duration,
+    paused: bool,
+    next: Instant,
+}
+
+impl Freezer {
+    /// start a freezer writing into <process>, fails if the thread can't be spawned
+    pub fn new<T>(process: T) -> std::io::Result<Self>
+    where
+        T: Mem + Send + Sync + 'static,
+    {
+        let (sender, failures) = std::sync::mpsc::sync_channel(MAX_PENDING_FAILURES);
+        let state = Frozen {
+            process,
+            entries: HashMap::new(),
+            failures: sender,
+        };
+        Ok(Self {
+            poller: Poller::spawn("poggers-freezer", state)?,
+            failures,
+        })
+    }
+    /// write <value> to <addr> every <interval> until removed
+    /// # Safety
+    /// the writes happen with [Mem::write_raw] from the freezer thread, <addr> must be safe to
+    /// write for as long as the value is frozen.
+    pub unsafe fn freeze<V: Pod>(
+        &self,
+        addr: impl Into<usize>,
+        value: V,
+        interval: Duration,
+    ) -> FreezeId {
+        self.freeze_bytes(addr, value.as_bytes().to_vec(), interval)
+    }
+    /// write <data> to <addr> every <interval> until removed
+    /// # Safety
+    /// see [Freezer::freeze]
+    pub unsafe fn freeze_bytes(
+        &self,
+        addr: impl Into<usize>,
+        data: Vec<u8>,
+        interval: Duration,
+    ) -> FreezeId {
+        let id = FreezeId(self.poller.next_id());
+        self.poller.send(Command::Freeze(
+            id,
+            Entry {
+                addr: addr.into(),
+                data,
+                interval,
+                paused: false,
+                next: Instant::now(),
+            },
+        ));
+        id
+    }
+    /// stop writing a frozen value until it is resumed
+    pub fn pause(&self, id: FreezeId) {
+        self.poller.send(Command::SetPaused(id, true));
+    }
+    /// start writing a paused value again
+    pub fn resume(&self, id: FreezeId) {
+        self.poller.send(Command::SetPaused(id, false));
+    }
+    /// stop writing a frozen value
+    pub fn remove(&self, id: FreezeId) {
+        self.poller.send(Command::Remove(id));
+    }
+    /// get the channel failed writes are reported on
+    pub fn get_failures(&self) -> &Receiver<FreezeFailure> {
+        &self.failures
+    }
+    /// get the failed writes since this was last called, without blocking
+    pub fn failures(&self) -> impl Iterator<Item = FreezeFailure> + '_ {
+        self.failures.try_iter()
+    }
+}
+
+impl<T: Mem + Send + 'static> Poll for Frozen<T> {
+    type Command = Command;
+
+    fn handle(&mut self, command: Command) {
+        match command {
+            Command::Freeze(id, entry) => {
+                self.entries.insert(id, entry);
+            }
+            Command::SetPaused(id, paused) => {
+                if let Some(entry) = self.entries.get_mut(&id) {
+                    entry.paused = paused;
+                    entry.next = Instant::now();
+                }
+            }
+            Command::Remove(id) => {
+                self.entries.remove(&id);
+            }
+        }
+    }
+    fn poll(&mut self) -> Option<Instant> {
+        let now = Instant::now();
+        for (id, entry) in self.entries.iter_mut() {
+            if entry.paused || entry.next > now {
+                continue;
+            }
+            // the address was promised to be writable by Freezer::freeze
+            if let Err(error) = unsafe { self.process.write_raw(entry.addr, &entry.data) } {
+                self.failures
+                    .try_send(FreezeFailure {
+                        id: *id,
+                        addr: entry.addr,
+                        error,
+                    })
+                    .ok();
+            }
+            entry.next = now + entry.interval;
+        }
+        self.entries
+            .values()
+            .filter(|entry| !entry.paused)
+            .map(|entry| entry.next)
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicU32, Ordering},
+        time::Duration,
+    };
+
+    use super::Freezer;
+    use crate::structures::process::Process;
+
+    #[test]
+    fn test_freezer() {
+        let value = Box::new(AtomicU32::new(0));
+        let addr = &*value as *const AtomicU32 as usize;
+        let freezer = Freezer::new(Process::find_pid(std::process::id()).unwrap()).unwrap();
+        let wait = || std::thread::sleep(Duration::from_millis(50));
+
+        let id = unsafe { freezer.freeze(addr, 1337u32, Duration::from_millis(1)) };
+        wait();
+        assert_eq!(value.load(Ordering::SeqCst), 1337);
+        value.store(0, Ordering::SeqCst);
+        wait();
+        assert_eq!(value.load(Ordering::SeqCst), 1337);
+
+        freezer.pause(id);
+        wait();
+        value.store(0, Ordering::SeqCst);
+        wait();
+        assert_eq!(value.load(Ordering::SeqCst), 0);
+
+        freezer.resume(id);
+        wait();
+        assert_eq!(value.load(Ordering::SeqCst), 1337);
+
+        freezer.remove(id);
+        wait();
+        value.store(0, Ordering::SeqCst);
+        wait();
+        assert_eq!(value.load(Ordering::SeqCst), 0);
+
+        let bad = unsafe { freezer.freeze(0usize, 1u32, Duration::from_millis(1)) };
+        let failure = freezer
+            .get_failures()
+            .recv_timeout(Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(failure.get_id(), bad);
+        assert_eq!(failure.get_addr(), 0);
+    }
+}
@@ -0,0 +1,539 @@
+use std::{
+    ops::Range,
+    path::Path,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use crate::{
+    sigscan::SigScan,
+    structures::{
+        modules::{Module, ModuleError},
+        process::implement::utils::ProcessUtils,
+        protections::Protections,
+        region::MemoryRegion,
+    },
+    traits::{Mem, MemError, PointerWidth, SafeMem},
+};
+
+/// where [MockProcess::virtual_alloc](Mem::virtual_alloc) places allocations without an address
+const ALLOC_BASE: usize = 0x7000_0000;
+const PAGE_SIZE: usize = 0x1000;
+
+/// a fake process backed by memory owned by this struct, for testing code built on poggers
+/// without a real process. clones share the same memory.
+/// ```rs
+/// let process = MockProcess::new("game")
+///     .with_module("libgame.so", 0x400000, code, Protections::new().with_read(true))
+///     .with_region(0x10000, vec![0; 0x1000], Protections::new().with_read(true).with_write(true))
+///     .with_read_failure(0x10800..0x10900);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockProcess {
+    state: Arc<RwLock<MockState>>,
+}
+
+#[derive(Debug)]
+struct MockState {
+    name: String,
+    pid: u32,
+    pointer_width: PointerWidth,
+    /// sorted by address, never overlapping
+    regions: Vec<MockRegion>,
+    read_failures: Vec<Range<usize>>,
+    write_failures: Vec<Range<usize>>,
+}
+
+#[derive(Debug, Clone)]
+struct MockRegion {
+    region: MemoryRegion,
+    data: Vec<u8>,
+}
+
+impl MockProcess {
+    /// create a process named <name> without any memory
+    pub fn new(name: &str) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(MockState {
+                name: name.to_string(),
+                pid: 1337,
+                pointer_width: PointerWidth::NATIVE,
+                regions: vec![],
+                read_failures: vec![],
+                write_failures: vec![],
+            })),
+        }
+    }
+    /// set the pid reported by [MockProcess::get_pid]
+    pub fn with_pid(self, pid: u32) -> Self {
+        self.state_mut().pid = pid;
+        self
+    }
+    /// set the pointer width reported by [Mem::pointer_width], to mock a 32-bit process
+    pub fn with_pointer_width(self, width: PointerWidth) -> Self {
+        self.state_mut().pointer_width = width;
+        self
+    }
+    /// map <data> at <start> as anonymous memory
+    /// # Panics
+    /// if the region overlaps an existing one
+    pub fn with_region(self, start: usize, data: impl Into<Vec<u8>>, prot: Protections) -> Self {
+        self.map(start, data.into(), prot, None);
+        self
+    }
+    /// map <data> at <base> as the module <name>, a module can be made of multiple regions by
+    /// calling this again with the same name
+    /// # Panics
+    /// if the region overlaps an existing one
+    pub fn with_module(
+        self,
+        name: &str,
+        base: usize,
+        data: impl Into<Vec<u8>>,
+        prot: Protections,
+    ) -> Self {
+        self.map(base, data.into(), prot, Some(Arc::from(Path::new(name))));
+        self
+    }
+    /// make every read touching <range> fail
+    pub fn with_read_failure(self, range: Range<usize>) -> Self {
+        self.state_mut().read_failures.push(range);
+        self
+    }
+    /// make every write touching <range> fail
+    pub fn with_write_failure(self, range: Range<usize>) -> Self {
+        self.state_mut().write_failures.push(range);
+        self
+    }
+    /// remove every injected read and write failure
+    pub fn clear_failures(&self) {
+        let mut state = self.state_mut();
+        state.read_failures.clear();
+        state.write_failures.clear();
+    }
+    /// get the pid of the process
+    pub fn get_pid(&self) -> u32 {
+        self.state().pid
+    }
+    /// get a copy of <size> bytes at <addr>, ignoring protections and injected failures
+    pub fn peek(&self, addr: usize, size: usize) -> Option<Vec<u8>> {
+        let state = self.state();
+        let region = state.region_at(addr)?;
+        let offset = addr - region.region.start;
+        region.data.get(offset..offset + size).map(<[u8]>::to_vec)
+    }
+    fn map(&self, start: usize, data: Vec<u8>, prot: Protections, path: Option<Arc<Path>>) {
+        let end = start + data.len();
+        let mut state = self.state_mut();
+        assert!(
+            !state
+                .regions
+                .iter()
+                .any(|region| region.region.start < end && start < region.region.end),
+            "mock region {:X}..{:X} overlaps an existing region",
+            start,
+            end
+        );
+        let idx = state
+            .regions
+            .partition_point(|region| region.region.start < start);
+        state.regions.insert(
+            idx,
+            MockRegion {
+                region: MemoryRegion {
+                    start,
+                    end,
+                    protections: prot,
+                    shared: false,
+                    offset: 0,
+                    path,
+                },
+                data,
+            },
+        );
+    }
+    fn state(&self) -> RwLockReadGuard<'_, MockState> {
+        self.state.read().unwrap_or_else(|e| e.into_inner())
+    }
+    fn state_mut(&self) -> RwLockWriteGuard<'_, MockState> {
+        self.state.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl MockState {
+    fn region_at(&self, addr: usize) -> Option<&MockRegion> {
+        let idx = self
+            .regions
+            .partition_point(|region| region.region.end <= addr);
+        self.regions
+            .get(idx)
+            .filter(|region| region.region.start <= addr)
+    }
+    fn region_at_mut(&mut self, addr: usize) -> Option<&mut MockRegion> {
+        let idx = self
+            .regions
+            .partition_point(|region| region.region.end <= addr);
+        self.regions
+            .get_mut(idx)
+            .filter(|region| region.region.start <= addr)
+    }
+    /// splits regions so that <addr> is the start of a region, if it lies inside one
+    fn split_at(&mut self, addr: usize) {
+        let Some(idx) = self
+            .regions
+            .iter()
+            .position(|region| region.region.start < addr && addr < region.region.end)
+        else {
+            return;
+        };
+        let region = &mut self.regions[idx];
+        let data = region.data.split_off(addr - region.region.start);
+        let mut upper = MockRegion {
+            region: region.region.clone(),
+            data,
+        };
+        region.region.end = addr;
+        upper.region.start = addr;
+        self.regions.insert(idx + 1, upper);
+    }
+    /// copies between <addr> and the memory of the process, stopping at the first byte which
+    /// can't be accessed. returns the amount of bytes copied.
+    fn transfer(
+        &mut self,
+        addr: usize,
+        size: usize,
+        write: bool,
+        mut copy: impl FnMut(&mut [u8], usize),
+    ) -> usize {
+        let failures = if write {
+            &self.write_failures
+        } else {
+            &self.read_failures
+        };
+        // stop at the first injected failure
+        let size = failures
+            .iter()
+            .filter(|range| range.start < addr + size && addr < range.end)
+            .map(|range| range.start.saturating_sub(addr))
+            .fold(size, usize::min);
+        let mut done = 0;
+        while done < size {
+            let at = addr + done;
+            let Some(region) = self.region_at_mut(at) else {
+                break;
+            };
+            let allowed = if write {
+                region.region.is_writable()
+            } else {
+                region.region.is_readable()
+            };
+            if !allowed {
+                break;
+            }
+            let offset = at - region.region.start;
+            let len = (size - done).min(region.data.len() - offset);
+            copy(&mut region.data[offset..offset + len], done);
+            done += len;
+        }
+        done
+    }
+}
+
+impl Mem for MockProcess {
+    fn pointer_width(&self) -> PointerWidth {
+        self.state().pointer_width
+    }
+    fn regions(&self) -> Result<impl Iterator<Item = MemoryRegion>, MemError> {
+        Ok(self
+            .state()
+            .regions
+            .iter()
+            .map(|region| region.region.clone())
+            .collect::<Vec<_>>()
+            .into_iter())
+    }
+    #[cfg(windows)]
+    unsafe fn raw_query(
+        &self,
+        addr: usize,
+    ) -> windows::Win32::System::Memory::MEMORY_BASIC_INFORMATION {
+        use windows::Win32::System::Memory::{MEMORY_BASIC_INFORMATION, MEM_COMMIT, MEM_FREE};
+        let state = self.state();
+        match state.region_at(addr) {
+            Some(region) => MEMORY_BASIC_INFORMATION {
+                BaseAddress: region.region.start as *mut std::ffi::c_void,
+                AllocationBase: region.region.start as *mut std::ffi::c_void,
+                RegionSize: region.region.get_size(),
+                State: MEM_COMMIT,
+                Protect: region.region.protections.native(),
+                AllocationProtect: region.region.protections.native(),
+                ..Default::default()
+            },
+            None => MEMORY_BASIC_INFORMATION {
+                BaseAddress: (addr & !(PAGE_SIZE - 1)) as *mut std::ffi::c_void,
+                RegionSize: PAGE_SIZE,
+                State: MEM_FREE,
+                ..Default::default()
+            },
+        }
+    }
+    /// changes the protections of every region within the range, splitting regions which are
+    /// partially covered. returns the previous protections of the region containing <addr>.
+    unsafe fn alter_protection(
+        &self,
+        addr: usize,
+        size: usize,
+        prot: Protections,
+    ) -> Result<Protections, MemError> {
+        let mut state = self.state_mut();
+        let old = state
+            .region_at(addr)
+            .ok_or(MemError::ProtectFailure(addr, size, prot))?
+            .region
+            .protections;
+        state.split_at(addr);
+        state.split_at(addr + size);
+        for region in state.regions.iter_mut() {
+            if addr <= region.region.start && region.region.end <= addr + size {
+                region.region.protections = prot;
+            }
+        }
+        Ok(old)
+    }
+    unsafe fn raw_read(&self, addr: usize, data: *mut u8, size: usize) -> Result<(), MemError> {
+        let out = std::slice::from_raw_parts_mut(data, size);
+        let transferred = self.state_mut().transfer(addr, size, false, |memory, at| {
+            out[at..at + memory.len()].copy_from_slice(memory)
+        });
+        match transferred {
+            x if x == size => Ok(()),
+            0 => Err(MemError::ReadFailure(addr)),
+            transferred => Err(MemError::PartialRead {
+                addr,
+                transferred,
+                size,
+            }),
+        }
+    }
+    unsafe fn raw_write(&self, addr: usize, data: *const u8, size: usize) -> Result<(), MemError> {
+        let input = std::slice::from_raw_parts(data, size);
+        let transferred = self.state_mut().transfer(addr, size, true, |memory, at| {
+            memory.copy_from_slice(&input[at..at + memory.len()])
+        });
+        match transferred {
+            x if x == size => Ok(()),
+            0 => Err(MemError::WriteFailure(addr)),
+            transferred => Err(MemError::PartialWrite {
+                addr,
+                transferred,
+                size,
+            }),
+        }
+    }
+    unsafe fn raw_virtual_alloc(
+        &self,
+        addr: Option<usize>,
+        size: usize,
+        prot: Protections,
+    ) -> Result<usize, MemError> {
+        let size = size.next_multiple_of(PAGE_SIZE);
+        let start = {
+            let state = self.state();
+            let overlaps = |start: usize| {
+                state
+                    .regions
+                    .iter()
+                    .any(|region| region.region.start < start + size && start < region.region.end)
+            };
+            match addr {
+                Some(addr) if overlaps(addr) => {
+                    return Err(MemError::AllocFailure(Some(addr), size))
+                }
+                Some(addr) => addr,
+                None => state
+                    .regions
+                    .iter()
+                    .map(|region| region.region.end.next_multiple_of(PAGE_SIZE))
+                    .chain(std::iter::once(ALLOC_BASE))
+                    .filter(|start| *start >= ALLOC_BASE && !overlaps(*start))
+                    .min()
+                    .ok_or(MemError::AllocFailure(None, size))?,
+            }
+        };
+        self.map(start, vec![0; size], prot, None);
+        Ok(start)
+    }
+    unsafe fn raw_virtual_free(&self, addr: usize, size: usize) -> Result<(), MemError> {
+        let mut state = self.state_mut();
+        if state.region_at(addr).is_none() {
+            return Err(MemError::FreeFailure(addr, size));
+        }
+        let size = size.next_multiple_of(PAGE_SIZE);
+        state.split_at(addr);
+        state.split_at(addr + size);
+        state
+            .regions
+            .retain(|region| !(addr <= region.region.start && region.region.end <= addr + size));
+        Ok(())
+    }
+}
+
+// memory is copied in and out of the owned buffers
+unsafe impl SafeMem for MockProcess {}
+
+impl SigScan for MockProcess {}
+
+impl ProcessUtils for MockProcess {
+    fn get_module(&self, name: &str) -> Result<Module<Self>, ModuleError>
+    where
+        Self: Sized + SigScan,
+    {
+        let state = self.state();
+        // like the linux implementation, <name> is either the full path or the file name
+        let path = state
+            .regions
+            .iter()
+            .filter_map(|region| region.region.get_path())
+            .find(|path| path.as_os_str() == name || path.file_name().is_some_and(|x| x == name))
+            .ok_or_else(|| ModuleError::NoModuleFound(name.to_string()))?;
+        let (start, end) = state
+            .regions
+            .iter()
+            .filter(|region| region.region.get_path() == Some(path))
+            .fold((usize::MAX, 0), |(start, end), region| {
+                (start.min(region.region.start), end.max(region.region.end))
+            });
+        Ok(Module {
+            name: Arc::from(
+                path.file_name()
+                    .map(|x| x.to_string_lossy())
+                    .unwrap_or_default()
+                    .as_ref(),
+            ),
+            path: Arc::from(path),
+            base_address: start,
+            end_address: end,
+            size: end - start,
+            handle: 0,
+            owner: Arc::new(self.clone()),
+        })
+    }
+    fn get_name(&self) -> String {
+        self.state().name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MockProcess;
+    use crate::{
+        sigscan::SigScan,
+        structures::{expr::Expression, process::implement::utils::ProcessUtils},
+        testing::{read_only, read_write},
+        traits::{Mem, MemError},
+    };
+
+    fn process() -> MockProcess {
+        let mut code = vec![0x90u8; 0x1000];
+        code[0x100..0x105].copy_from_slice(&[0x48, 0x8B, 0x05, 0x10, 0x20]);
+        MockProcess::new("game")
+            .with_module("libgame.so", 0x400000, code, read_only())
+            .with_module("libgame.so", 0x401000, vec![0; 0x1000], read_write())
+            .with_region(0x10000, vec![0; 0x2000], read_write())
+    }
+
+    #[test]
+    fn test_read_write() {
+        let proc = process();
+        unsafe {
+            proc.write(0x10010, &0x1337u32).unwrap();
+            assert_eq!(proc.read::<u32>(0x10010).unwrap(), 0x1337);
+            assert_eq!(proc.clone().read::<u32>(0x10010).unwrap(), 0x1337);
+
+            assert!(matches!(
+                proc.write(0x400000, &1u32),
+                Err(MemError::WriteFailure(0x400000))
+            ));
+            assert!(matches!(
+                proc.read_sized(0x11FF0, 0x20),
+                Err(MemError::PartialRead {
+                    transferred: 0x10,
+                    ..
+                })
+            ));
+            assert!(proc.read::<u32>(0x5000).is_err());
+
+            let alloc = proc.virtual_alloc(None, 0x10, read_write()).unwrap();
+            let addr = alloc.get_addr();
+            proc.write(addr, &5u64).unwrap();
+            assert_eq!(proc.query(addr).unwrap().get_size(), 0x1000);
+            alloc.free();
+            assert!(proc.read::<u64>(addr).is_err());
+
+            let old = proc.alter_protection(0x10000, 0x1000, read_only()).unwrap();
+            assert_eq!(format!("{old:?}"), format!("{:?}", read_write()));
+            assert!(proc.write(0x10000, &1u8).is_err());
+            proc.write(0x11000, &1u8).unwrap();
+            assert_eq!(proc.regions().unwrap().count(), 4);
+        }
+    }
+
+    #[test]
+    fn test_failures() {
+        let proc = process().with_read_failure(0x10100..0x10200);
+        unsafe {
+            assert!(proc.read::<u32>(0x10000).is_ok());
+            assert!(matches!(
+                proc.read_sized(0x100F0, 0x20),
+                Err(MemError::PartialRead {
+                    transferred: 0x10,
+                    ..
+                })
+            ));
+            assert!(proc.write(0x10100, &1u32).is_ok());
+            proc.clear_failures();
+            assert_eq!(proc.read::<u32>(0x10100).unwrap(), 1);
+        }
+    }
+
+    #[test]
+    fn test_modules() {
+        let proc = process();
+        assert_eq!(proc.get_name(), "game");
+        let module = proc.get_module("libgame.so").unwrap();
+        assert_eq!(module.get_base_address(), 0x400000);
+        assert_eq!(module.get_size(), 0x2000);
+        assert!(proc.get_module("missing.so").is_err());
+
+        let code = proc.peek(0x400000, 0x1000).unwrap();
+        assert_eq!(
+            proc.scan("48 8B 05 ? 20", code.iter()).unwrap(),
+            Some(0x100)
+        );
+        assert!(proc.scan("48 8B 0", code.iter()).is_err());
+        assert_eq!(module.scan("48 8B 05 ? 20").unwrap(), Some(0x400100));
+        assert_eq!(module.scan("48 8B 05 ? 21").unwrap(), None);
+
+        unsafe { proc.write(0x401010, &0xDEADBEEFu32).unwrap() };
+        assert_eq!(module.scan_value(&0xDEADBEEFu32).unwrap(), Some(0x401010));
+        assert_eq!(module.scan_value(&0xFEEDu16).unwrap(), None);
+
+        unsafe {
+            proc.write(0x401008, &0x10000usize).unwrap();
+            let addr = Expression::parse("libgame.so + 0x1008 -> 0x10")
+                .unwrap()
+                .resolve(&proc)
+                .unwrap();
+            assert_eq!(addr.get_addr(), 0x10010);
+        }
+
+        let paths = MockProcess::new("game")
+            .with_module("/usr/lib/libfoo.so", 0x7000, vec![0; 0x1000], read_only())
+            .with_module("/opt/libfoo.so", 0x9000, vec![0; 0x1000], read_only());
+        let module = paths.get_module("libfoo.so").unwrap();
+        assert_eq!(module.get_name(), "libfoo.so");
+        assert_eq!(module.get_base_address(), 0x7000);
+        assert_eq!(module.get_size(), 0x1000);
+        let module = paths.get_module("/opt/libfoo.so").unwrap();
+        assert_eq!(module.get_base_address(), 0x9000);
+    }
+}
@@ -1,20 +1,40 @@
 /// wrapper around a address
 pub mod addr;
-#[feature(modules)]
+/// processes restored from ELF core files
+#[cfg(target_os = "linux")]
+pub mod core_dump;
+/// cheat engine style address expressions
+pub mod expr;
+/// keeping values pinned from a background thread
+pub mod freezer;
+/// an in memory process for testing, requires the `mock` feature
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 /// a module in a process
 pub mod modules;
-#[feature(processes)]
+/// finding pointer paths to an address
+pub mod pointer_scan;
+/// a background thread which polls state and is fed commands
+pub(crate) mod poller;
 /// an alternative to create_snapshot, just list through all processes running
 pub mod proc_list;
-#[feature(processes)]
 /// process
 pub mod process;
 /// protections for memory
+// bitfield-struct generates undocumented from_bits/into_bits
+#[cfg_attr(unix, allow(missing_docs))]
 pub mod protections;
+/// regions of mapped memory in a process
+pub mod region;
+/// copies of process memory which can be compared
+pub mod snapshot;
+/// first-scan/next-scan searches for values
+pub mod value_scan;
 /// helper for allocated virtual memory
 pub mod virtalloc;
+/// notifications for changes of process memory
+pub mod watch;
 
 #[cfg(windows)]
-#[feature(snapshot)]
 /// a wrapper for the win32 snapshottool api
 pub mod create_snapshot;
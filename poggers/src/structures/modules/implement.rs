@@ -1,4 +1,11 @@
-use crate::{sigscan::SigScan, traits::MemError};
+use crate::{
+    sigscan::{
+        pattern::{Pattern, ToPattern},
+        readable_chunks, scan_parallel, unique, ScanChunk, SigScan,
+    },
+    structures::region::RegionFilter,
+    traits::{MemError, Pod},
+};
 
 use super::Module;
 
@@ -6,59 +13,79 @@ impl<T> Module<T>
 where
     T: SigScan,
 {
-    #[cfg(windows)]
-    /// scan for a pattern in the module
-    pub fn scan(&self, pattern: &str) -> Result<Option<usize>, MemError> {
-        use windows::Win32::System::Memory::{MEM_COMMIT, PAGE_NOACCESS};
-        let mut addr = self.get_base_address();
-        let owner = self.get_owner();
-        // this should have no performance implications cause get_base_address.. is const
-        while addr < self.get_base_address() + self.get_size() {
-            unsafe {
-                let query = owner.raw_query(addr);
-                if query.State != MEM_COMMIT || query.Protect == PAGE_NOACCESS {
-                    addr += query.RegionSize;
-                    continue;
-                }
-                let mut page = [0u8; WIN_PAGE_SIZE];
-                owner.raw_read(addr, &mut page as *mut u8, WIN_PAGE_SIZE)?;
-                let scan_res = owner.scan(pattern, page.iter());
-
-                if let Some(result) = scan_res {
-                    println!("Found pattern at {:#x}", scan_res.unwrap());
-                    return Ok(Some(addr + result));
-                }
-                addr += WIN_PAGE_SIZE;
-            }
-        }
-        Ok(None)
+    /// the readable parts of the module split into [ScanChunk]s
+    fn readable_chunks(&self) -> Result<Vec<ScanChunk>, MemError> {
+        let filter =
+            RegionFilter::new().with_range(self.get_base_address(), self.get_end_address());
+        readable_chunks(self.get_owner(), &filter)
     }
-    #[cfg(windows)]
-    /// scan for a value of <V> in the module
-    pub fn scan_value<V>(&self, val: &V) -> Result<Option<usize>, MemError> {
-        use windows::Win32::System::Memory::{MEM_COMMIT, PAGE_NOACCESS};
-        let mut addr = self.get_base_address();
-
-        while addr < self.get_base_address() + self.get_size() {
-            unsafe {
-                let query = self.get_owner().raw_query(addr);
-                if query.State != MEM_COMMIT || query.Protect == PAGE_NOACCESS {
-                    addr += query.RegionSize;
-                    continue;
-                }
-                let mut page = [0u8; WIN_PAGE_SIZE];
-                self.get_owner()
-                    .raw_read(addr, &mut page as *mut u8, WIN_PAGE_SIZE)?;
-                let scan_res = self.get_owner().scan_batch_value(val, &page);
-
-                if let Some(result) = scan_res {
-                    println!("Found pattern at {:#x}", scan_res.unwrap());
-                    return Ok(Some(addr + result));
-                }
-                addr += WIN_PAGE_SIZE;
-            }
-        }
-        Ok(None)
+    /// scan for every match of a pattern in the module, either a `&str` or a compiled
+    /// [Pattern](crate::sigscan::pattern::Pattern). unreadable parts of the module are skipped.
+    /// ```rs
+    /// for call in module.scan_all("E8 ? ? ? ? 84 C0")? { .. }
+    /// ```
+    pub fn scan_all(
+        &self,
+        pattern: impl ToPattern,
+    ) -> Result<impl Iterator<Item = usize>, MemError> {
+        let pattern = pattern.to_pattern()?;
+        let found = self
+            .readable_chunks()?
+            .iter()
+            .flat_map(|chunk| unsafe { chunk.scan(self.get_owner(), &pattern) })
+            .collect::<Vec<_>>();
+        Ok(found.into_iter())
+    }
+    /// scan for a pattern which has to match exactly once in the module, erroring with
+    /// [MemError::PatternNotUnique] and the amount of matches if it is ambiguous
+    pub fn scan_unique(&self, pattern: impl ToPattern) -> Result<usize, MemError> {
+        unique(self.scan_all(pattern)?)
+    }
+    /// scan for the first match of a pattern in the module, either a `&str` or a compiled
+    /// [Pattern](crate::sigscan::pattern::Pattern). unreadable parts of the module are skipped.
+    /// ```rs
+    /// let found = module.scan("48 8B 05 ? ? ? ? 48 85 C0")?;
+    /// ```
+    pub fn scan(&self, pattern: impl ToPattern) -> Result<Option<usize>, MemError> {
+        let pattern = pattern.to_pattern()?;
+        Ok(self.readable_chunks()?.iter().find_map(|chunk| {
+            unsafe { chunk.scan(self.get_owner(), &pattern) }
+                .first()
+                .copied()
+        }))
+    }
+    /// scan for the first value of <V> in the module, only at addresses aligned for <V>.
+    /// unreadable parts of the module are skipped.
+    /// ```rs
+    /// let found = module.scan_value(&1337u32)?;
+    /// ```
+    pub fn scan_value<V: Pod>(&self, val: &V) -> Result<Option<usize>, MemError> {
+        let bytes = val.as_bytes();
+        let pattern = Pattern::from_masked(bytes.to_vec(), vec![0xFF; bytes.len()])?;
+        let align = std::mem::align_of::<V>();
+        Ok(self.readable_chunks()?.iter().find_map(|chunk| {
+            unsafe { chunk.scan(self.get_owner(), &pattern) }
+                .into_iter()
+                .find(|addr| addr % align == 0)
+        }))
+    }
+}
+impl<T> Module<T>
+where
+    T: SigScan + Sync,
+{
+    /// scan for every match of a pattern in the module like [Module::scan_all], splitting the
+    /// work across <threads> threads, or one per core if 0. the matches are sorted.
+    /// ```rs
+    /// let calls = module.scan_all_parallel("E8 ? ? ? ? 84 C0", 0)?;
+    /// ```
+    pub fn scan_all_parallel(
+        &self,
+        pattern: impl ToPattern,
+        threads: usize,
+    ) -> Result<Vec<usize>, MemError> {
+        let pattern = pattern.to_pattern()?;
+        let chunks = self.readable_chunks()?;
+        Ok(unsafe { scan_parallel(self.get_owner(), &pattern, &chunks, threads) })
     }
 }
-pub(super) const WIN_PAGE_SIZE: usize = 0x1000;
@@ -11,6 +11,7 @@ pub struct Module<T: SigScan> {
     pub(crate) base_address: usize,
     pub(crate) end_address: usize,
     pub(crate) size: usize,
+    #[cfg_attr(not(windows), allow(dead_code))]
     pub(crate) handle: isize,
     pub(crate) owner: Arc<T>,
 }
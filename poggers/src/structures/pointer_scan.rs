@@ -0,0 +1,513 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    io::{BufRead, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::{
+    sigscan::SigScan,
+    structures::{addr::Address, process::implement::utils::ProcessUtils},
+    traits::{Mem, MemError, PointerWidth},
+};
+
+/// finds pointer paths from module static data to a (usually heap allocated) target address,
+/// like cheat engine's pointer scanner.
+/// ```rs
+/// let results = PointerScanner::new(player_health).with_max_depth(4).scan(&process)?;
+/// results.save("health.ptrs")?;
+/// // after restarting the game, keep only the paths which still lead to the new address
+/// let results = PointerScanResults::load("health.ptrs")?.rescan(&process, new_player_health);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PointerScanner {
+    target: usize,
+    max_depth: usize,
+    max_offset: usize,
+    max_results: usize,
+    max_nodes: usize,
+    alignment: Option<usize>,
+}
+
+/// a pointer path, `[[module + module_offset] + offsets[0]] + offsets[1] ...`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PointerPath {
+    pub(crate) module: String,
+    pub(crate) module_offset: usize,
+    pub(crate) offsets: Vec<usize>,
+}
+
+/// the paths found by a [PointerScanner]
+#[derive(Debug, Clone, Default)]
+pub struct PointerScanResults {
+    pub(crate) paths: Vec<PointerPath>,
+}
+
+/// Pointer scan failures
+#[derive(Debug, thiserror::Error)]
+pub enum PointerScanError {
+    /// Unable to query or read the process
+    #[error("{0}")]
+    MemError(#[from] MemError),
+    /// Unable to save or load results
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    /// A saved pointer path which doesn't parse
+    #[error("invalid pointer path on line {line}: '{text}'")]
+    InvalidPath {
+        /// the line number, starting at 1
+        line: usize,
+        /// the text of the line
+        text: String,
+    },
+}
+
+/// a static region a path can be rooted in
+struct StaticRegion {
+    start: usize,
+    end: usize,
+    module: String,
+    module_base: usize,
+}
+
+/// the state of a single scan
+struct Walk<'a> {
+    map: &'a PointerMap,
+    offsets: Vec<usize>,
+    results: PointerScanResults,
+    /// addresses known to have no path, with the depth which was left when they were walked
+    dead_ends: HashMap<usize, usize>,
+    visited: usize,
+}
+
+/// every pointer found in the process, sorted by value
+struct PointerMap {
+    /// (value, address the value is stored at)
+    pointers: Vec<(usize, usize)>,
+    statics: Vec<StaticRegion>,
+}
+
+impl PointerScanner {
+    /// create a scanner looking for paths to <target>
+    /// defaults to a depth of 4, offsets up to 0x1000, 10000 results and 10 million pointers
+    /// visited
+    pub const fn new(target: usize) -> Self {
+        Self {
+            target,
+            max_depth: 4,
+            max_offset: 0x1000,
+            max_results: 10000,
+            max_nodes: 10_000_000,
+            alignment: None,
+        }
+    }
+    /// the maximum amount of offsets in a path
+    pub const fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+    /// the maximum offset added to each pointer
+    pub const fn with_max_offset(mut self, max_offset: usize) -> Self {
+        self.max_offset = max_offset;
+        self
+    }
+    /// stop scanning after finding this many paths
+    pub const fn with_max_results(mut self, max_results: usize) -> Self {
+        self.max_results = max_results;
+        self
+    }
+    /// stop scanning after following this many pointers, which bounds the work done on memory
+    /// full of pointers
+    pub const fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+    /// only consider pointers stored at addresses aligned to <alignment>, defaults to the
+    /// [pointer width](Mem::pointer_width) of the process
+    pub const fn with_alignment(mut self, alignment: usize) -> Self {
+        self.alignment = Some(alignment);
+        self
+    }
+    /// scan the readable and writable memory of <process> for pointer paths to the target.
+    /// paths are rooted in regions backed by a file (and the anonymous region directly after one,
+    /// which holds its `.bss`).
+    /// # Safety
+    /// reads every writable region of the process.
+    pub unsafe fn scan<T: Mem>(&self, process: &T) -> Result<PointerScanResults, MemError> {
+        let width = process.pointer_width();
+        let alignment = self.alignment.unwrap_or(width.size()).max(1);
+        let map = PointerMap::build(process, width, alignment)?;
+        let mut walk = Walk {
+            map: &map,
+            offsets: vec![],
+            results: PointerScanResults::default(),
+            dead_ends: HashMap::new(),
+            visited: 0,
+        };
+        self.walk(&mut walk, self.target);
+        Ok(walk.results)
+    }
+    /// find every pointer to somewhere just before <addr>, recording the static ones.
+    /// returns if any path was found, or the scan was cut short.
+    fn walk(&self, walk: &mut Walk, addr: usize) -> bool {
+        let depth_left = self.max_depth - walk.offsets.len();
+        if depth_left == 0 {
+            return false;
+        }
+        // the paths below an address don't depend on how it was reached
+        if walk
+            .dead_ends
+            .get(&addr)
+            .is_some_and(|depth| *depth >= depth_left)
+        {
+            return false;
+        }
+        let map = walk.map;
+        let lowest = addr.saturating_sub(self.max_offset);
+        let first = map.pointers.partition_point(|(value, _)| *value < lowest);
+        let mut found = false;
+        for (value, at) in map.pointers[first..]
+            .iter()
+            .take_while(|(value, _)| *value <= addr)
+        {
+            if walk.results.paths.len() >= self.max_results || walk.visited >= self.max_nodes {
+                return true;
+            }
+            walk.visited += 1;
+            walk.offsets.push(addr - value);
+            if let Some(region) = map.static_region(*at) {
+                walk.results.paths.push(PointerPath {
+                    module: region.module.clone(),
+                    module_offset: at - region.module_base,
+                    offsets: walk.offsets.iter().rev().copied().collect(),
+                });
+                found = true;
+            }
+            found |= self.walk(walk, *at);
+            walk.offsets.pop();
+        }
+        if !found {
+            walk.dead_ends.insert(addr, depth_left);
+        }
+        found
+    }
+}
+
+impl PointerMap {
+    unsafe fn build<T: Mem>(
+        process: &T,
+        width: PointerWidth,
+        alignment: usize,
+    ) -> Result<Self, MemError> {
+        let regions = process.regions()?.collect::<Vec<_>>();
+        let readable = regions
+            .iter()
+            .filter(|region| region.is_readable())
+            .map(|region| (region.get_start(), region.get_end()))
+            .collect::<Vec<_>>();
+        let is_readable = |value: usize| {
+            let idx = readable.partition_point(|(_, end)| *end <= value);
+            readable.get(idx).is_some_and(|(start, _)| *start <= value)
+        };
+
+        // the module base is its lowest mapping, the same as get_module
+        let mut bases: HashMap<String, usize> = HashMap::new();
+        for region in regions.iter() {
+            if let Some(name) = region.get_path().and_then(|path| path.file_name()) {
+                let base = bases
+                    .entry(name.to_string_lossy().to_string())
+                    .or_insert(usize::MAX);
+                *base = (*base).min(region.get_start());
+            }
+        }
+
+        let mut statics: Vec<StaticRegion> = vec![];
+        let mut pointers = vec![];
+        let mut last_module: Option<(String, usize, usize)> = None;
+        for region in regions.iter() {
+            let module = match region.get_path() {
+                // pseudo paths such as [heap] or [stack]
+                Some(path) if path.to_string_lossy().starts_with('[') => None,
+                Some(path) => path.file_name().map(|name| {
+                    let name = name.to_string_lossy().to_string();
+                    let base = bases[&name];
+                    (name, base)
+                }),
+                // .bss is mapped anonymously right after the module
+                None => last_module
+                    .as_ref()
+                    .filter(|(_, _, end)| *end == region.get_start())
+                    .map(|(name, base, _)| (name.clone(), *base)),
+            };
+            last_module = module
+                .clone()
+                .map(|(name, base)| (name, base, region.get_end()));
+
+            if !region.is_readable() || !region.is_writable() {
+                continue;
+            }
+            let Ok(data) = process.read_sized(region.get_start(), region.get_size()) else {
+                continue;
+            };
+            if let Some((module, module_base)) = module {
+                statics.push(StaticRegion {
+                    start: region.get_start(),
+                    end: region.get_end(),
+                    module,
+                    module_base,
+                });
+            }
+            let first = region.get_start().next_multiple_of(alignment) - region.get_start();
+            let size = width.size();
+            for offset in (first..data.len().saturating_sub(size - 1)).step_by(alignment) {
+                let value = width.decode(&data[offset..]);
+                if value != 0 && is_readable(value) {
+                    pointers.push((value, region.get_start() + offset));
+                }
+            }
+        }
+        pointers.sort_unstable();
+        Ok(Self { pointers, statics })
+    }
+    fn static_region(&self, addr: usize) -> Option<&StaticRegion> {
+        let idx = self.statics.partition_point(|region| region.end <= addr);
+        self.statics.get(idx).filter(|region| region.start <= addr)
+    }
+}
+
+impl PointerPath {
+    /// get the name of the module the path is rooted in
+    pub fn get_module(&self) -> &str {
+        &self.module
+    }
+    /// get the offset of the first pointer from the module base
+    pub const fn get_module_offset(&self) -> usize {
+        self.module_offset
+    }
+    /// get the offsets added after each dereference
+    pub fn get_offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+    /// follow the path in <process>, returning the address it leads to
+    /// # Safety
+    /// reads every pointer along the path.
+    pub unsafe fn resolve<'a, T>(&self, process: &'a T) -> Result<Address<'a, T>, MemError>
+    where
+        T: SigScan + ProcessUtils,
+    {
+        let base = process.get_module(&self.module)?.get_base_address();
+        let offsets = self
+            .offsets
+            .iter()
+            .map(|offset| *offset as isize)
+            .collect::<Vec<_>>();
+        Address::new(process, base + self.module_offset).follow(&offsets)
+    }
+}
+
+/// formatted as an expression which [Expression](super::expr::Expression) can parse
+impl Display for PointerPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "\"{}\"+{:#X}", self.module, self.module_offset)?;
+        for offset in &self.offsets {
+            write!(f, " -> {:#X}", offset)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PointerPath {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_hex = |x: &str| {
+            let x = x.trim();
+            usize::from_str_radix(x.strip_prefix("0x").or(x.strip_prefix("0X")).ok_or(())?, 16)
+                .map_err(|_| ())
+        };
+        let (module, rest) = s
+            .trim()
+            .strip_prefix('"')
+            .ok_or(())?
+            .split_once('"')
+            .ok_or(())?;
+        let mut parts = rest.trim().strip_prefix('+').ok_or(())?.split("->");
+        Ok(Self {
+            module: module.to_string(),
+            module_offset: parse_hex(parts.next().ok_or(())?)?,
+            offsets: parts.map(parse_hex).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl PointerScanResults {
+    /// get the paths which were found
+    pub fn paths(&self) -> &[PointerPath] {
+        &self.paths
+    }
+    /// the amount of paths
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+    /// if no paths were found
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+    /// keep only the paths which resolve to <target> in <process>, e.g. after the process was
+    /// restarted and the target moved.
+    /// # Safety
+    /// reads every pointer along every path.
+    pub unsafe fn rescan<T>(&self, process: &T, target: usize) -> Self
+    where
+        T: SigScan + ProcessUtils,
+    {
+        Self {
+            paths: self
+                .paths
+                .iter()
+                .filter(|path| {
+                    path.resolve(process)
+                        .is_ok_and(|addr| addr.get_addr() == target)
+                })
+                .cloned()
+                .collect(),
+        }
+    }
+    /// write the paths to <writer>, one per line
+    pub fn write_to(&self, mut writer: impl Write) -> Result<(), PointerScanError> {
+        for path in &self.paths {
+            writeln!(writer, "{}", path)?;
+        }
+        Ok(())
+    }
+    /// read paths written by [PointerScanResults::write_to]
+    pub fn read_from(reader: impl BufRead) -> Result<Self, PointerScanError> {
+        let mut paths = vec![];
+        for (line, text) in reader.lines().enumerate() {
+            let text = text?;
+            if text.trim().is_empty() {
+                continue;
+            }
+            paths.push(text.parse().map_err(|_| PointerScanError::InvalidPath {
+                line: line + 1,
+                text: text.clone(),
+            })?);
+        }
+        Ok(Self { paths })
+    }
+    /// save the paths to the file at <path>
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PointerScanError> {
+        self.write_to(std::io::BufWriter::new(std::fs::File::create(path)?))
+    }
+    /// load paths saved with [PointerScanResults::save]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PointerScanError> {
+        Self::read_from(std::io::BufReader::new(std::fs::File::open(path)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::{PointerPath, PointerScanResults, PointerScanner};
+    use crate::{
+        structures::{mock::MockProcess, modules::ModuleError, process::Process},
+        testing::read_write,
+        traits::MemError,
+    };
+
+    #[test]
+    fn test_path_roundtrip() {
+        let path = PointerPath {
+            module: "libgame.so".into(),
+            module_offset: 0x1A2B30,
+            offsets: vec![0x18, 0x4],
+        };
+        assert_eq!(path.to_string(), "\"libgame.so\"+0x1A2B30 -> 0x18 -> 0x4");
+        assert_eq!(path.to_string().parse::<PointerPath>().unwrap(), path);
+        assert!("libgame.so+0x10".parse::<PointerPath>().is_err());
+        assert!(matches!(
+            unsafe { path.resolve(&MockProcess::new("game")) },
+            Err(MemError::ModuleError(ModuleError::NoModuleFound(name))) if name == "libgame.so"
+        ));
+
+        let mut saved = vec![];
+        let results = PointerScanResults {
+            paths: vec![path.clone(), path],
+        };
+        results.write_to(&mut saved).unwrap();
+        let loaded = PointerScanResults::read_from(&saved[..]).unwrap();
+        assert_eq!(loaded.paths(), results.paths());
+    }
+
+    #[test]
+    fn test_dense_heap() {
+        // every slot points into the heap, so each address has 128 candidates
+        let heap = (0..0x2000usize / 8)
+            .flat_map(|i| (0x10000 + i * 8).to_le_bytes())
+            .collect::<Vec<_>>();
+        let proc = MockProcess::new("game").with_region(0x10000, heap, read_write());
+        let scanner = PointerScanner::new(0x11F00)
+            .with_max_depth(8)
+            .with_max_offset(0x400)
+            .with_alignment(8);
+        assert!(unsafe { scanner.scan(&proc) }.unwrap().is_empty());
+
+        let mut root = vec![0u8; 0x1000];
+        root[0x10..0x18].copy_from_slice(&0x10000usize.to_le_bytes());
+        let proc = proc.with_module("libgame.so", 0x400000, root, read_write());
+        let results = unsafe {
+            PointerScanner::new(0x10800)
+                .with_max_depth(4)
+                .with_max_offset(0x400)
+                .with_alignment(8)
+                .with_max_nodes(100)
+                .scan(&proc)
+        }
+        .unwrap();
+        assert!(!results.is_empty() && results.len() <= 100);
+        assert_eq!(results.paths()[0].get_module_offset(), 0x10);
+    }
+
+    #[cfg(any(target_os = "linux", windows))]
+    #[test]
+    fn test_scan() {
+        static ROOT: AtomicUsize = AtomicUsize::new(0);
+        let exe = std::env::current_exe().unwrap();
+        let exe = exe.file_name().unwrap().to_str().unwrap().to_string();
+        let proc = Process::find_pid(std::process::id()).unwrap();
+
+        // ROOT -> +0x10 -> +0x8 -> target
+        let build = || {
+            let target = Box::new([0u64; 4]);
+            let mut inner = Box::new([0usize; 4]);
+            inner[1] = &target[0] as *const u64 as usize;
+            let mut outer = Box::new([0usize; 4]);
+            outer[2] = &inner[0] as *const usize as usize;
+            ROOT.store(&outer[0] as *const usize as usize, Ordering::SeqCst);
+            let target_addr = &target[1] as *const u64 as usize;
+            (target, inner, outer, target_addr)
+        };
+        let (_target, _inner, _outer, target) = build();
+
+        unsafe {
+            let results = PointerScanner::new(target)
+                .with_max_depth(3)
+                .with_max_offset(0x20)
+                .scan(&proc)
+                .unwrap();
+            let expected = results
+                .paths()
+                .iter()
+                .find(|path| path.get_module() == exe && path.get_offsets() == [0x10, 0x8, 0x8])
+                .expect("the path through ROOT was not found");
+            assert_eq!(expected.resolve(&proc).unwrap().get_addr(), target);
+
+            // "restart", everything moves but the path through ROOT still leads to the target
+            let (_target, _inner, _outer, target) = build();
+            let rescanned = results.rescan(&proc, target);
+            assert!(rescanned.paths().contains(expected));
+            assert!(rescanned.len() <= results.len());
+        }
+    }
+}
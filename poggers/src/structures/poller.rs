@@ -0,0 +1,147 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{Receiver, RecvTimeoutError, Sender, TryRecvError},
+    },
+    thread::JoinHandle,
+    time::Instant,
+};
+
+/// state owned by the thread of a [Poller]
+pub(crate) trait Poll: Send + 'static {
+    /// what is sent to the thread
+    type Command: Send + 'static;
+    /// apply a command sent to the poller
+    fn handle(&mut self, command: Self::Command);
+    /// do any work which is due, returning when to be polled next.
+    /// none waits for the next command.
+    fn poll(&mut self) -> Option<Instant>;
+}
+
+/// a background thread polling a [Poll] and feeding it commands.
+/// the thread stops when the poller is dropped.
+pub(crate) struct Poller<C> {
+    commands: Option<Sender<C>>,
+    next_id: AtomicU64,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<C: Send + 'static> Poller<C> {
+    /// spawn a thread named <name> polling <state>
+    pub(crate) fn spawn<P: Poll<Command = C>>(name: &str, state: P) -> std::io::Result<Self> {
+        let (commands, receiver) = std::sync::mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name(name.into())
+            .spawn(move || run(state, receiver))?;
+        Ok(Self {
+            commands: Some(commands),
+            next_id: AtomicU64::new(0),
+            thread: Some(thread),
+        })
+    }
+    /// get a new id, unique for this poller
+    pub(crate) fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+    /// send <command> to the thread
+    pub(crate) fn send(&self, command: C) {
+        if let Some(commands) = &self.commands {
+            // the thread only exits once the poller is dropped
+            commands.send(command).ok();
+        }
+    }
+}
+
+impl<C> std::fmt::Debug for Poller<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Poller")
+            .field("next_id", &self.next_id)
+            .field("thread", &self.thread)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C> Drop for Poller<C> {
+    fn drop(&mut self) {
+        // closing the channel stops the thread
+        self.commands.take();
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+fn run<P: Poll>(mut state: P, commands: Receiver<P::Command>) {
+    loop {
+        // handle everything queued up before polling, so a stream of commands can't starve it
+        loop {
+            match commands.try_recv() {
+                Ok(command) => state.handle(command),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return,
+            }
+        }
+        let command = match state.poll() {
+            Some(next) => commands.recv_timeout(next.saturating_duration_since(Instant::now())),
+            None => commands.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match command {
+            Ok(command) => state.handle(command),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::{Duration, Instant},
+    };
+
+    use super::{Poll, Poller};
+
+    struct Counter {
+        polls: Arc<AtomicUsize>,
+        next: Instant,
+    }
+
+    impl Poll for Counter {
+        type Command = ();
+
+        fn handle(&mut self, _: ()) {}
+        fn poll(&mut self) -> Option<Instant> {
+            let now = Instant::now();
+            if self.next <= now {
+                self.polls.fetch_add(1, Ordering::SeqCst);
+                self.next = now + Duration::from_millis(1);
+            }
+            Some(self.next)
+        }
+    }
+
+    #[test]
+    fn test_commands_dont_starve_polls() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let poller = Poller::spawn(
+            "poggers-test",
+            Counter {
+                polls: polls.clone(),
+                next: Instant::now() + Duration::from_millis(1),
+            },
+        )
+        .unwrap();
+        let end = Instant::now() + Duration::from_millis(50);
+        while Instant::now() < end {
+            poller.send(());
+            std::thread::sleep(Duration::from_micros(100));
+        }
+        assert!(polls.load(Ordering::SeqCst) > 5);
+        assert_eq!(poller.next_id(), 0);
+        assert_eq!(poller.next_id(), 1);
+    }
+}
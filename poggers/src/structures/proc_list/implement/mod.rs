@@ -17,9 +17,6 @@ pub use windows::*;
 
 #[cfg(test)]
 mod tests {
-    use tracing_subscriber::{layer::SubscriberExt, Registry};
-    use tracing_tree::HierarchicalLayer;
-
     use crate::structures::proc_list::{ProcList, ProcessList};
 
     #[test]
@@ -1,31 +1,63 @@
-use libc::{__errno_location, c_void, process_vm_readv, process_vm_writev};
+use std::{fs::OpenOptions, marker::PhantomData, os::unix::fs::FileExt, sync::Arc};
+
+use libc::{c_void, process_vm_readv, process_vm_writev};
 use tracing::instrument;
 
 use crate::{
     sigscan::SigScan,
     structures::{
+        modules::ModuleError,
         process::{implement::utils::ProcessUtils, External, Process, ProcessError, U32OrString},
         protections::Protections,
+        region::{implement::read_maps, MemoryRegion},
     },
-    traits::Mem,
+    traits::{Mem, MemError, PointerWidth, ReadRequest},
 };
 
+/// how writes into an external process are performed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WriteMethod {
+    /// use process_vm_writev, retrying through `/proc/<pid>/mem` when writing into a
+    /// region which is mapped but not writable (e.g. `.text` or `.rodata`)
+    #[default]
+    Auto,
+    /// only use process_vm_writev, this fails on non-writable regions
+    ProcessVm,
+    /// always write through `/proc/<pid>/mem`, which ignores page protections.
+    /// requires ptrace access to the process
+    ProcMem,
+}
+
+/// fetches errno of the last failed call
+#[inline]
+fn last_errno() -> i32 {
+    std::io::Error::last_os_error()
+        .raw_os_error()
+        .unwrap_or_default()
+}
+
 impl Mem for Process<External> {
-    /// will always return unsupported.
-    #[inline]
+    fn pointer_width(&self) -> PointerWidth {
+        self.pointer_width
+    }
+    fn regions(&self) -> Result<impl Iterator<Item = MemoryRegion>, crate::traits::MemError> {
+        Ok(read_maps(&format!("/proc/{}/maps", self.pid))?.into_iter())
+    }
+    /// calls mprotect inside the process through a [ptrace session](super::ptrace::PtraceSession),
+    /// returning the previous protections of the region containing <addr>.
+    /// only supported on x86_64.
     unsafe fn alter_protection(
         &self,
-        _addr: usize,
-        _size: usize,
-        _prot: Protections,
+        addr: usize,
+        size: usize,
+        prot: Protections,
     ) -> Result<Protections, crate::traits::MemError> {
-        // let result = mprotect(addr as *mut c_void, size, prot.native());
-        // if result == -1 {
-        //     Err(crate::traits::MemError::ProtectFailure(addr, size, prot))
-        // } else {
-        //     Ok(Protections::from_native(result))
-        // }
-        Err(crate::traits::MemError::Unsupported)
+        let old = *self.query(addr)?.get_protections();
+        let res = self.remote_syscall(libc::SYS_mprotect, &[addr, size, prot.native() as usize])?;
+        if res < 0 {
+            return Err(MemError::ProtectFailure(addr, size, prot));
+        }
+        Ok(old)
     }
 
     unsafe fn raw_read(
@@ -43,20 +75,192 @@ impl Mem for Process<External> {
             iov_len: size,
         }];
 
-        let res = process_vm_readv(self.pid as i32, local.as_ptr(), 2, remote.as_ptr(), 1, 0);
-        // TODO @pozm:  handle errors;
-        println!("res: {}", res);
-        let errno = __errno_location().read();
-        println!("errno: {}", errno);
+        let res = process_vm_readv(
+            self.pid as i32,
+            local.as_ptr(),
+            local.len() as _,
+            remote.as_ptr(),
+            remote.len() as _,
+            0,
+        );
+        if res == -1 {
+            return Err(MemError::ReadOsFailure {
+                addr,
+                errno: last_errno(),
+            });
+        }
+        if res as usize != size {
+            return Err(MemError::PartialRead {
+                addr,
+                transferred: res as usize,
+                size,
+            });
+        }
         Ok(())
     }
+    /// batches the requests into process_vm_readv calls of up to `UIO_MAXIOV` iovecs.
+    /// the kernel stops at the first request it can't fully read, that request is retried on its
+    /// own to find its error and the batch continues after it.
+    unsafe fn read_scatter(&self, requests: &mut [ReadRequest<'_>]) -> Vec<Result<(), MemError>> {
+        let mut results: Vec<Result<(), MemError>> = Vec::with_capacity(requests.len());
+        while results.len() < requests.len() {
+            let start = results.len();
+            let end = (start + libc::UIO_MAXIOV as usize).min(requests.len());
+            let batch = &mut requests[start..end];
+            let local = batch
+                .iter_mut()
+                .map(|request| libc::iovec {
+                    iov_base: request.buf.as_mut_ptr() as *mut c_void,
+                    iov_len: request.buf.len(),
+                })
+                .collect::<Vec<_>>();
+            let remote = batch
+                .iter()
+                .map(|request| libc::iovec {
+                    iov_base: request.addr as *mut c_void,
+                    iov_len: request.buf.len(),
+                })
+                .collect::<Vec<_>>();
 
+            let res = process_vm_readv(
+                self.pid as i32,
+                local.as_ptr(),
+                local.len() as _,
+                remote.as_ptr(),
+                remote.len() as _,
+                0,
+            );
+            let mut transferred = if res == -1 {
+                let errno = last_errno();
+                // anything but a bad address will fail every other request as well
+                if errno != libc::EFAULT {
+                    results.extend(requests[start..].iter().map(|request| {
+                        Err(MemError::ReadOsFailure {
+                            addr: request.addr,
+                            errno,
+                        })
+                    }));
+                    break;
+                }
+                0
+            } else {
+                res as usize
+            };
+            for request in batch.iter_mut() {
+                if request.buf.len() > transferred {
+                    break;
+                }
+                transferred -= request.buf.len();
+                results.push(Ok(()));
+            }
+            // the kernel stopped inside the batch, read the request it stopped at on its own
+            if results.len() < end {
+                let request = &mut requests[results.len()];
+                results.push(self.raw_read(
+                    request.addr,
+                    request.buf.as_mut_ptr(),
+                    request.buf.len(),
+                ));
+            }
+        }
+        results
+    }
+
+    /// writes using the process' [WriteMethod]. with [WriteMethod::Auto], writes into
+    /// non-writable regions are retried through `/proc/<pid>/mem`
     unsafe fn raw_write(
         &self,
         addr: usize,
         data: *const u8,
         size: usize,
     ) -> Result<(), crate::traits::MemError> {
+        match self.write_method {
+            WriteMethod::ProcessVm => self.process_vm_write(addr, data, size),
+            WriteMethod::ProcMem => self.proc_mem_write(addr, data, size),
+            WriteMethod::Auto => match self.process_vm_write(addr, data, size) {
+                Err(MemError::WriteOsFailure {
+                    errno: libc::EFAULT,
+                    ..
+                }) if self.is_read_only(addr) => self.proc_mem_write(addr, data, size),
+                Err(MemError::PartialWrite { transferred, .. })
+                    if self.is_read_only(addr + transferred) =>
+                {
+                    self.proc_mem_write(
+                        addr + transferred,
+                        data.add(transferred),
+                        size - transferred,
+                    )
+                }
+                res => res,
+            },
+        }
+    }
+    /// calls mmap inside the process through a [ptrace session](super::ptrace::PtraceSession).
+    /// if <addr> is given the mapping is placed exactly there, failing if it is already in use.
+    /// only supported on x86_64.
+    unsafe fn raw_virtual_alloc(
+        &self,
+        addr: Option<usize>,
+        size: usize,
+        prot: Protections,
+    ) -> Result<usize, crate::traits::MemError> {
+        let mut flags = libc::MAP_PRIVATE | libc::MAP_ANONYMOUS;
+        if addr.is_some() {
+            flags |= libc::MAP_FIXED_NOREPLACE;
+        }
+        let res = self.remote_syscall(
+            libc::SYS_mmap,
+            &[
+                addr.unwrap_or_default(),
+                size,
+                prot.native() as usize,
+                flags as usize,
+                usize::MAX, // fd -1
+                0,
+            ],
+        )?;
+        // mmap returns -errno on failure
+        if (-4095..0).contains(&res) {
+            return Err(MemError::AllocFailure(addr, size));
+        }
+        // kernels before 4.17 ignore MAP_FIXED_NOREPLACE and treat <addr> as a hint
+        if addr.is_some_and(|addr| addr != res as usize) {
+            self.remote_syscall(libc::SYS_munmap, &[res as usize, size])?;
+            return Err(MemError::AllocFailure(addr, size));
+        }
+        Ok(res as usize)
+    }
+    /// calls munmap inside the process through a [ptrace session](super::ptrace::PtraceSession).
+    /// only supported on x86_64.
+    unsafe fn raw_virtual_free(
+        &self,
+        addr: usize,
+        size: usize,
+    ) -> Result<(), crate::traits::MemError> {
+        let res = self.remote_syscall(libc::SYS_munmap, &[addr, size])?;
+        if res < 0 {
+            return Err(MemError::FreeFailure(addr, size));
+        }
+        Ok(())
+    }
+}
+impl Process<External> {
+    /// run a syscall inside the process with a temporary ptrace session
+    #[cfg(target_arch = "x86_64")]
+    unsafe fn remote_syscall(&self, nr: libc::c_long, args: &[usize]) -> Result<i64, MemError> {
+        Ok(self.ptrace()?.syscall(nr, args)?)
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    unsafe fn remote_syscall(&self, _nr: libc::c_long, _args: &[usize]) -> Result<i64, MemError> {
+        Err(MemError::Unsupported)
+    }
+    /// write using process_vm_writev, which respects the protections of the target
+    unsafe fn process_vm_write(
+        &self,
+        addr: usize,
+        data: *const u8,
+        size: usize,
+    ) -> Result<(), MemError> {
         let local = [libc::iovec {
             iov_base: data as *mut c_void,
             iov_len: size,
@@ -66,37 +270,70 @@ impl Mem for Process<External> {
             iov_len: size,
         }];
 
-        let res = process_vm_writev(self.pid as i32, local.as_ptr(), 2, remote.as_ptr(), 1, 0);
-        // TODO @pozm:  handle errors;
-
+        let res = process_vm_writev(
+            self.pid as i32,
+            local.as_ptr(),
+            local.len() as _,
+            remote.as_ptr(),
+            remote.len() as _,
+            0,
+        );
+        if res == -1 {
+            return Err(MemError::WriteOsFailure {
+                addr,
+                errno: last_errno(),
+            });
+        }
+        if res as usize != size {
+            return Err(MemError::PartialWrite {
+                addr,
+                transferred: res as usize,
+                size,
+            });
+        }
         Ok(())
     }
-    /// will always return unsupported.
-    #[inline]
-    unsafe fn raw_virtual_alloc(
+    /// write through `/proc/<pid>/mem`, which ignores page protections (like ptrace's POKEDATA)
+    unsafe fn proc_mem_write(
         &self,
-        _addr: Option<usize>,
-        _size: usize,
-        _prot: Protections,
-    ) -> Result<usize, crate::traits::MemError> {
-        // let addr_or_null = addr.map(|x| x as *mut c_void ).unwrap_or(std::ptr::null_mut());
-        // let to_proc = format!("/proc/{}/maps",self.pid);
-
-        // let fder = libc::open(to_proc.as_ptr() as *const i8, libc::O_RDONLY, 0);
-
-        // let addr = libc::mmap(addr_or_null, size, prot.native(), libc::MAP_PRIVATE, fder, 0);
-
-        // Ok(addr as usize)
-        Err(crate::traits::MemError::Unsupported)
+        addr: usize,
+        data: *const u8,
+        size: usize,
+    ) -> Result<(), MemError> {
+        let to_os_failure = |e: std::io::Error| MemError::WriteOsFailure {
+            addr,
+            errno: e.raw_os_error().unwrap_or_default(),
+        };
+        let file = OpenOptions::new()
+            .write(true)
+            .open(format!("/proc/{}/mem", self.pid))
+            .map_err(to_os_failure)?;
+        let data = std::slice::from_raw_parts(data, size);
+        file.write_all_at(data, addr as u64).map_err(to_os_failure)
     }
-    /// will always return unsupported.
-    #[inline]
-    unsafe fn raw_virtual_free(
-        &self,
-        _addr: usize,
-        _size: usize,
-    ) -> Result<(), crate::traits::MemError> {
-        Err(crate::traits::MemError::Unsupported)
+    /// if <addr> is mapped but not writable
+    fn is_read_only(&self, addr: usize) -> bool {
+        self.query(addr)
+            .map(|region| !region.is_writable())
+            .unwrap_or(false)
+    }
+    /// get the method used for writing to this process
+    pub const fn get_write_method(&self) -> WriteMethod {
+        self.write_method
+    }
+    /// set the method used for writing to this process
+    pub fn set_write_method(&mut self, method: WriteMethod) {
+        self.write_method = method;
+    }
+    /// use <method> for writing to this process
+    pub const fn with_write_method(mut self, method: WriteMethod) -> Self {
+        self.write_method = method;
+        self
+    }
+    /// override the detected pointer width of this process
+    pub const fn with_pointer_width(mut self, width: PointerWidth) -> Self {
+        self.pointer_width = width;
+        self
     }
 }
 impl Process<External> {
@@ -119,9 +356,10 @@ impl Process<External> {
                 continue;
             }
             let path = file.path().join("comm");
-            let f = std::fs::read_to_string(path).map_err(|_| {
-                ProcessError::UnableToFindProcess(U32OrString::String(name.to_string()))
-            })?;
+            // the process may have exited since the directory was listed
+            let Ok(f) = std::fs::read_to_string(path) else {
+                continue;
+            };
             if f.trim() == name {
                 let pid = file.file_name().to_str().unwrap().parse::<u32>().unwrap();
                 return Self::new(pid);
@@ -143,10 +381,13 @@ impl Process<External> {
 
     #[instrument]
     fn new(pid: u32) -> Result<Self, crate::structures::process::ProcessError> {
-        let name = std::fs::read_to_string(format!("/proc/{}/comm", pid))
+        std::fs::read_to_string(format!("/proc/{}/comm", pid))
             .map_err(|_| ProcessError::UnableToFindProcess(U32OrString::U32(pid)))?;
         Ok(Self {
             pid,
+            write_method: WriteMethod::default(),
+            // the exe link can't be read without ptrace access, assume the process matches us
+            pointer_width: super::detect_pointer_width(pid).unwrap_or(PointerWidth::NATIVE),
             mrk: std::marker::PhantomData,
         })
     }
@@ -164,6 +405,217 @@ impl ProcessUtils for Process<External> {
     where
         Self: Sized + SigScan,
     {
-        todo!()
+        let regions = self
+            .regions()
+            .map_err(|_| ModuleError::NoModuleFound(name.to_string()))?;
+        super::module_from_regions(Arc::new(self.clone()), name, regions)
+    }
+}
+impl Clone for Process<External> {
+    fn clone(&self) -> Self {
+        Self {
+            pid: self.pid,
+            write_method: self.write_method,
+            pointer_width: self.pointer_width,
+            mrk: PhantomData,
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::WriteMethod;
+    use crate::{
+        structures::process::Process,
+        traits::{Mem, MemError, PointerWidth, ReadRequest},
+    };
+
+    #[test]
+    fn test_pointer_width() {
+        let proc = Process::find_pid(std::process::id()).unwrap();
+        assert_eq!(
+            super::super::detect_pointer_width(std::process::id()),
+            Some(PointerWidth::NATIVE)
+        );
+        assert_eq!(proc.pointer_width(), PointerWidth::NATIVE);
+        let proc = proc.with_pointer_width(PointerWidth::Bits32);
+        let value = 0x1122_3344_5566_7788u64;
+        unsafe {
+            assert_eq!(
+                proc.read_ptr(&value as *const u64 as usize).unwrap(),
+                0x5566_7788
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let proc = Process::find_pid(std::process::id()).unwrap();
+        let err = unsafe { proc.read::<u32>(0).unwrap_err() };
+        assert_eq!(err.errno(), Some(libc::EFAULT));
+
+        unsafe {
+            let pages = libc::mmap(
+                std::ptr::null_mut(),
+                0x2000,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            ) as usize;
+            libc::munmap((pages + 0x1000) as *mut libc::c_void, 0x1000);
+            let err = proc.read_sized(pages + 0xFF0, 0x20).unwrap_err();
+            assert!(matches!(
+                err,
+                MemError::PartialRead {
+                    transferred: 0x10,
+                    size: 0x20,
+                    ..
+                }
+            ));
+            libc::munmap(pages as *mut libc::c_void, 0x1000);
+        }
+    }
+    #[test]
+    fn test_read_scatter() {
+        static VALUES: [u32; 4] = [1, 2, 3, 4];
+        let proc = Process::find_pid(std::process::id()).unwrap();
+        let addrs = [
+            &VALUES[0] as *const u32 as usize,
+            &VALUES[1] as *const u32 as usize,
+            0,
+            &VALUES[2] as *const u32 as usize,
+            &VALUES[3] as *const u32 as usize,
+        ];
+        let results = unsafe { proc.read_many::<u32>(&addrs) };
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0].as_ref().unwrap(), &1);
+        assert_eq!(results[1].as_ref().unwrap(), &2);
+        assert_eq!(results[2].as_ref().unwrap_err().errno(), Some(libc::EFAULT));
+        assert_eq!(results[3].as_ref().unwrap(), &3);
+        assert_eq!(results[4].as_ref().unwrap(), &4);
+
+        // more requests than fit into one batch, with a bad one right after the first batch
+        let mut addrs = vec![&VALUES[1] as *const u32 as usize; libc::UIO_MAXIOV as usize + 2];
+        addrs[libc::UIO_MAXIOV as usize] = 0;
+        let results = unsafe { proc.read_many::<u32>(&addrs) };
+        assert_eq!(results.len(), addrs.len());
+        assert!(results[..libc::UIO_MAXIOV as usize]
+            .iter()
+            .all(|x| x.as_ref().is_ok_and(|x| *x == 2)));
+        assert!(results[libc::UIO_MAXIOV as usize].is_err());
+        assert_eq!(results.last().unwrap().as_ref().unwrap(), &2);
+
+        // empty requests always succeed
+        let mut empty = [0u8; 0];
+        let mut requests = [ReadRequest::new(0, &mut empty)];
+        assert!(unsafe { proc.read_scatter(&mut requests) }[0].is_ok());
+    }
+    #[test]
+    fn test_read_only_write() {
+        let proc = Process::find_pid(std::process::id())
+            .unwrap()
+            .with_write_method(WriteMethod::ProcessVm);
+        unsafe {
+            let addr = libc::mmap(
+                std::ptr::null_mut(),
+                0x1000,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            ) as usize;
+            (addr as *mut u32).write(0x1337);
+            libc::mprotect(addr as *mut libc::c_void, 0x1000, libc::PROT_READ);
+
+            let err = proc.write(addr, &0x7331u32).unwrap_err();
+            assert_eq!(err.errno(), Some(libc::EFAULT));
+            assert_eq!(proc.read::<u32>(addr).unwrap(), 0x1337);
+
+            let proc = proc.with_write_method(WriteMethod::Auto);
+            proc.write(addr, &0x7331u32).unwrap();
+            assert_eq!(proc.read::<u32>(addr).unwrap(), 0x7331);
+
+            libc::munmap(addr as *mut libc::c_void, 0x1000);
+        }
+    }
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_remote_alloc() {
+        use crate::structures::protections::Protections;
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let proc = Process::find_pid(child.id()).unwrap();
+        unsafe {
+            let alloc = proc
+                .virtual_alloc(
+                    None,
+                    0x1000,
+                    Protections::new().with_read(true).with_write(true),
+                )
+                .unwrap();
+            let addr = alloc.get_addr();
+            proc.write(addr, &0x1337u32).unwrap();
+            assert_eq!(proc.read::<u32>(addr).unwrap(), 0x1337);
+
+            let old = proc
+                .alter_protection(addr, 0x1000, Protections::new().with_read(true))
+                .unwrap();
+            assert!(old.write());
+            assert!(!proc.query(addr).unwrap().get_protections().write());
+
+            alloc.free();
+            assert!(proc.query(addr).is_err());
+
+            let read_write = Protections::new().with_read(true).with_write(true);
+            let fixed = proc.virtual_alloc(Some(addr), 0x1000, read_write).unwrap();
+            assert_eq!(fixed.get_addr(), addr);
+            assert!(proc.virtual_alloc(Some(addr), 0x1000, read_write).is_err());
+            fixed.free();
+        }
+        // the process should still be alive and well
+        assert!(child.try_wait().unwrap().is_none());
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_module_scan() {
+        use crate::structures::process::implement::utils::ProcessUtils;
+
+        static MARKER: [u8; 16] = [
+            0x9A, 0x3C, 0x71, 0xE4, 0x5D, 0x0B, 0xC6, 0x2F, 0x88, 0x13, 0xF7, 0x6E, 0xA1, 0x54,
+            0xD9, 0x37,
+        ];
+        static VALUE: u64 = 0x7A3C_91E4_5D0B_C62F;
+        let exe = std::env::current_exe().unwrap();
+        let name = exe.file_name().unwrap().to_str().unwrap();
+        let marker = std::hint::black_box(&MARKER).as_ptr() as usize;
+        let value = std::hint::black_box(&VALUE) as *const u64 as usize;
+
+        let external = Process::find_pid(std::process::id()).unwrap();
+        let module = external.get_module(name).unwrap();
+        let found = module
+            .scan_all("9A 3C 71 E4 5D 0B C6 2F 88 13 F7 6E A1 54 D9 37")
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert!(found.contains(&marker));
+        assert!(module.scan("9A 3C 71 E4 ? ? C6 2F").unwrap().is_some());
+        assert!(module
+            .scan("9A 3C 71 E4 ? ? C6 3F 00 00 00")
+            .unwrap()
+            .is_none());
+        assert!(module.scan_value(&VALUE).unwrap().is_some());
+
+        let internal = Process::this_process();
+        let module = internal.get_module(name).unwrap();
+        assert!(module
+            .scan_all("9A 3C 71 E4 5D 0B C6 2F 88 13 F7 6E A1 54 D9 37")
+            .unwrap()
+            .any(|x| x == marker));
+        assert!(module.scan_value(&VALUE).unwrap().is_some());
+        assert!((module.get_base_address()..module.get_end_address()).contains(&value));
     }
 }
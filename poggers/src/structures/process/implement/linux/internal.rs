@@ -1,15 +1,22 @@
 use tracing::instrument;
 
+use std::{marker::PhantomData, sync::Arc};
+
 use crate::{
     sigscan::SigScan,
     structures::{
-        process::{implement::utils::ProcessUtils, External, Internal, Process},
+        modules::ModuleError,
+        process::{implement::utils::ProcessUtils, Internal, Process},
         protections::Protections,
+        region::{implement::read_maps, MemoryRegion},
     },
-    traits::Mem,
+    traits::{Mem, PointerWidth},
 };
 
 impl Mem for Process<Internal> {
+    fn regions(&self) -> Result<impl Iterator<Item = MemoryRegion>, crate::traits::MemError> {
+        Ok(read_maps("/proc/self/maps")?.into_iter())
+    }
     unsafe fn alter_protection(
         &self,
         addr: usize,
@@ -80,9 +87,10 @@ impl Mem for Process<Internal> {
 }
 impl Process<Internal> {
     pub(crate) fn new() -> Self {
-        let name = std::fs::read_to_string("/proc/self/comm").unwrap();
         Self {
             pid: unsafe { libc::getpid() } as u32,
+            write_method: Default::default(),
+            pointer_width: PointerWidth::NATIVE,
             mrk: Default::default(),
         }
     }
@@ -101,6 +109,19 @@ impl ProcessUtils for Process<Internal> {
     where
         Self: Sized + SigScan,
     {
-        todo!()
+        let regions = self
+            .regions()
+            .map_err(|_| ModuleError::NoModuleFound(name.to_string()))?;
+        super::module_from_regions(Arc::new(self.clone()), name, regions)
+    }
+}
+impl Clone for Process<Internal> {
+    fn clone(&self) -> Self {
+        Self {
+            pid: self.pid,
+            write_method: self.write_method,
+            pointer_width: self.pointer_width,
+            mrk: PhantomData,
+        }
     }
 }
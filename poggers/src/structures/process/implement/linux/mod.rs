@@ -1,6 +1,72 @@
 /// for external usage
-#[feature(external)]
 pub mod external;
 /// for internal usage
-#[feature(internal)]
 pub mod internal;
+/// ptrace sessions for external processes
+pub mod ptrace;
+
+use std::{io::Read, path::Path, sync::Arc};
+
+use crate::{
+    sigscan::SigScan,
+    structures::{
+        modules::{Module, ModuleError},
+        region::MemoryRegion,
+    },
+    traits::PointerWidth,
+};
+
+/// reads the pointer width of <pid> from the ELF class of its executable.
+/// none if the executable can't be read or isn't an ELF file
+pub(crate) fn detect_pointer_width(pid: u32) -> Option<PointerWidth> {
+    let mut ident = [0u8; 5];
+    std::fs::File::open(format!("/proc/{}/exe", pid))
+        .and_then(|mut file| file.read_exact(&mut ident))
+        .ok()?;
+    if &ident[..4] != b"\x7fELF" {
+        return None;
+    }
+    match ident[4] {
+        1 => Some(PointerWidth::Bits32),
+        2 => Some(PointerWidth::Bits64),
+        _ => None,
+    }
+}
+
+/// builds a module out of the mappings of the file named <name> (or with the full path <name>)
+pub(crate) fn module_from_regions<T: SigScan>(
+    owner: Arc<T>,
+    name: &str,
+    regions: impl Iterator<Item = MemoryRegion>,
+) -> Result<Module<T>, ModuleError> {
+    let mut path: Option<Arc<Path>> = None;
+    let (mut start, mut end) = (usize::MAX, 0);
+    for region in regions {
+        let Some(region_path) = region.get_path() else {
+            continue;
+        };
+        let matches =
+            region_path.as_os_str() == name || region_path.file_name().is_some_and(|x| x == name);
+        if !matches {
+            continue;
+        }
+        start = start.min(region.get_start());
+        end = end.max(region.get_end());
+        path.get_or_insert_with(|| Arc::from(region_path));
+    }
+    let path = path.ok_or_else(|| ModuleError::NoModuleFound(name.to_string()))?;
+    Ok(Module {
+        name: Arc::from(
+            path.file_name()
+                .map(|x| x.to_string_lossy())
+                .unwrap_or_default()
+                .as_ref(),
+        ),
+        path,
+        base_address: start,
+        end_address: end,
+        size: end - start,
+        handle: 0,
+        owner,
+    })
+}
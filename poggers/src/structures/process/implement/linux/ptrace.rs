@@ -0,0 +1,421 @@
+use std::marker::PhantomData;
+
+use tracing::{debug, instrument};
+
+use crate::structures::process::{External, Process, ProcessError, U32OrString};
+
+/// a thread which is being traced by a [PtraceSession]
+#[derive(Debug)]
+struct TracedThread {
+    tid: i32,
+    /// signals which arrived while running a syscall in the thread, redelivered on resume
+    pending_signals: Vec<i32>,
+}
+
+/// a ptrace session on an external process.
+/// every thread of the process is seized upon creation and the process is detached from when this
+/// is dropped.
+/// # Notes
+/// ptrace ties the session to the os thread which created it, so a session can not be sent
+/// between threads.
+#[derive(Debug)]
+pub struct PtraceSession<'a> {
+    process: &'a Process<External>,
+    threads: Vec<TracedThread>,
+    stopped: bool,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Process<External> {
+    /// attach to the process with ptrace, stopping all of its threads.
+    /// the process is resumed and detached from when the session is dropped.
+    #[instrument]
+    pub fn ptrace(&self) -> Result<PtraceSession<'_>, ProcessError> {
+        let mut session = PtraceSession {
+            process: self,
+            threads: vec![],
+            stopped: false,
+            _not_send: PhantomData,
+        };
+        session.seize_new_threads()?;
+        session.stop()?;
+        Ok(session)
+    }
+}
+
+impl<'a> PtraceSession<'a> {
+    /// get the process this session is attached to
+    pub const fn get_process(&self) -> &'a Process<External> {
+        self.process
+    }
+    /// if the threads of the process are currently stopped
+    pub const fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+    /// get the thread ids which are being traced
+    pub fn threads(&self) -> impl Iterator<Item = i32> + '_ {
+        self.threads.iter().map(|thread| thread.tid)
+    }
+    /// stop every thread of the process, attaching to any threads created since the last stop
+    #[instrument(skip(self))]
+    pub fn stop(&mut self) -> Result<(), ProcessError> {
+        if self.stopped {
+            return Ok(());
+        }
+        self.seize_new_threads()?;
+        // threads which can't be interrupted have exited
+        self.threads
+            .retain(|thread| unsafe { ptrace(libc::PTRACE_INTERRUPT, thread.tid, 0, 0) }.is_ok());
+        self.threads
+            .retain(|thread| match wait_for_interrupt(thread.tid) {
+                Ok(()) => true,
+                Err(_) => {
+                    debug!("thread {} exited while stopping", thread.tid);
+                    false
+                }
+            });
+        self.stopped = true;
+        Ok(())
+    }
+    /// resume every thread of the process
+    #[instrument(skip(self))]
+    pub fn resume(&mut self) -> Result<(), ProcessError> {
+        if !self.stopped {
+            return Ok(());
+        }
+        let pid = self.process.pid as i32;
+        for thread in self.threads.iter_mut() {
+            restart(pid, libc::PTRACE_CONT, thread)?;
+        }
+        self.stopped = false;
+        Ok(())
+    }
+    /// detach from the process, resuming it
+    pub fn detach(self) {
+        // handled by drop
+    }
+    /// get the registers of a traced thread, the session must be stopped
+    #[cfg(target_arch = "x86_64")]
+    pub fn get_regs(&self, tid: i32) -> Result<libc::user_regs_struct, ProcessError> {
+        let mut regs: libc::user_regs_struct = unsafe { std::mem::zeroed() };
+        unsafe {
+            ptrace(
+                libc::PTRACE_GETREGS,
+                tid,
+                0,
+                &mut regs as *mut libc::user_regs_struct as usize,
+            )
+        }?;
+        Ok(regs)
+    }
+    /// set the registers of a traced thread, the session must be stopped
+    #[cfg(target_arch = "x86_64")]
+    pub fn set_regs(&self, tid: i32, regs: &libc::user_regs_struct) -> Result<(), ProcessError> {
+        unsafe {
+            ptrace(
+                libc::PTRACE_SETREGS,
+                tid,
+                0,
+                regs as *const libc::user_regs_struct as usize,
+            )
+        }?;
+        Ok(())
+    }
+    /// execute syscall <nr> with <args> inside the process, returning the raw result (negative
+    /// errno on failure).
+    /// a stopped thread is hijacked to run a `syscall` instruction placed at its instruction
+    /// pointer, its registers and the overwritten bytes are restored afterwards.
+    /// # Safety
+    /// the syscall is executed by the target, it can do anything the target could.
+    #[cfg(target_arch = "x86_64")]
+    #[instrument(skip(self))]
+    pub unsafe fn syscall(
+        &mut self,
+        nr: libc::c_long,
+        args: &[usize],
+    ) -> Result<i64, ProcessError> {
+        const SYSCALL: u16 = 0x050F;
+        if args.len() > 6 {
+            return Err(ProcessError::TooManySyscallArgs(args.len()));
+        }
+        self.stop()?;
+        let pid = self.process.pid as i32;
+        let thread = self
+            .threads
+            .iter()
+            .position(|thread| thread.tid == pid)
+            .unwrap_or_default();
+        let tid = self
+            .threads
+            .get(thread)
+            .ok_or(ProcessError::UnableToOpenProcess(U32OrString::U32(
+                self.process.pid,
+            )))?
+            .tid;
+
+        let saved_regs = self.get_regs(tid)?;
+        let rip = saved_regs.rip as usize;
+        let saved_code = peek(tid, rip)?;
+        ptrace(
+            libc::PTRACE_POKETEXT,
+            tid,
+            rip,
+            (saved_code & !0xFFFF) | SYSCALL as usize,
+        )?;
+
+        let mut regs = saved_regs;
+        let mut args = args.iter().map(|x| *x as u64).chain(std::iter::repeat(0));
+        regs.rax = nr as u64;
+        regs.rdi = args.next().unwrap_or_default();
+        regs.rsi = args.next().unwrap_or_default();
+        regs.rdx = args.next().unwrap_or_default();
+        regs.r10 = args.next().unwrap_or_default();
+        regs.r8 = args.next().unwrap_or_default();
+        regs.r9 = args.next().unwrap_or_default();
+        // stop the kernel from restarting a syscall the thread was interrupted in
+        regs.orig_rax = u64::MAX;
+
+        let result = self.set_regs(tid, &regs).and_then(|_| {
+            loop {
+                ptrace(libc::PTRACE_SINGLESTEP, tid, 0, 0)?;
+                match wait_for_stop(tid)? {
+                    libc::SIGTRAP => break,
+                    0 => {}
+                    // some other signal arrived first, hand it back once we resume
+                    signal => self.threads[thread].pending_signals.push(signal),
+                }
+            }
+            Ok(self.get_regs(tid)?.rax as i64)
+        });
+
+        ptrace(libc::PTRACE_POKETEXT, tid, rip, saved_code)?;
+        self.set_regs(tid, &saved_regs)?;
+        result
+    }
+    /// seize every thread of the process which is not yet traced
+    fn seize_new_threads(&mut self) -> Result<(), ProcessError> {
+        let pid = self.process.pid;
+        let tasks = std::fs::read_dir(format!("/proc/{}/task", pid))
+            .map_err(|_| ProcessError::UnableToOpenProcess(U32OrString::U32(pid)))?;
+        for tid in tasks.filter_map(|x| x.ok()?.file_name().to_str()?.parse::<i32>().ok()) {
+            if self.threads.iter().any(|thread| thread.tid == tid) {
+                continue;
+            }
+            match unsafe { ptrace(libc::PTRACE_SEIZE, tid, 0, 0) } {
+                Ok(_) => self.threads.push(TracedThread {
+                    tid,
+                    pending_signals: vec![],
+                }),
+                // the thread exited before we could seize it
+                Err(ProcessError::PtraceFailure { errno, .. }) if errno == libc::ESRCH => {}
+                Err(ProcessError::PtraceFailure { errno, .. }) if errno == libc::EPERM => {
+                    return Err(ProcessError::PtraceDenied(pid, ptrace_scope()));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for PtraceSession<'_> {
+    fn drop(&mut self) {
+        // seized threads can only be detached from while stopped
+        if self.stop().is_err() {
+            debug!("unable to stop threads before detaching");
+        }
+        let pid = self.process.pid as i32;
+        for mut thread in self.threads.drain(..) {
+            restart(pid, libc::PTRACE_DETACH, &mut thread).ok();
+        }
+    }
+}
+
+/// restart <thread> with <request>, delivering every pending signal. ptrace can only hand one
+/// signal back, the others are queued to the thread again with tgkill.
+fn restart(pid: i32, request: libc::c_uint, thread: &mut TracedThread) -> Result<(), ProcessError> {
+    let mut signals = std::mem::take(&mut thread.pending_signals).into_iter();
+    unsafe { ptrace(request, thread.tid, 0, signals.next().unwrap_or(0) as usize) }?;
+    for signal in signals {
+        unsafe { libc::syscall(libc::SYS_tgkill, pid, thread.tid, signal) };
+    }
+    Ok(())
+}
+
+/// thin wrapper around the ptrace syscall, mapping failures into [ProcessError::PtraceFailure]
+pub(crate) unsafe fn ptrace(
+    request: libc::c_uint,
+    tid: i32,
+    addr: usize,
+    data: usize,
+) -> Result<libc::c_long, ProcessError> {
+    let res = libc::ptrace(request, tid, addr, data);
+    if res == -1 {
+        return Err(ProcessError::PtraceFailure {
+            request,
+            tid,
+            errno: std::io::Error::last_os_error()
+                .raw_os_error()
+                .unwrap_or_default(),
+        });
+    }
+    Ok(res)
+}
+
+/// read a word of memory from <tid> with PTRACE_PEEKTEXT
+unsafe fn peek(tid: i32, addr: usize) -> Result<usize, ProcessError> {
+    // -1 is a valid result, so errno has to be checked instead
+    *libc::__errno_location() = 0;
+    let res = libc::ptrace(libc::PTRACE_PEEKTEXT, tid, addr, 0);
+    let errno = *libc::__errno_location();
+    if res == -1 && errno != 0 {
+        return Err(ProcessError::PtraceFailure {
+            request: libc::PTRACE_PEEKTEXT,
+            tid,
+            errno,
+        });
+    }
+    Ok(res as usize)
+}
+
+/// wait until <tid> reaches the event-stop of a PTRACE_INTERRUPT. signals which arrive before it
+/// are handed straight back to the thread, the interrupt stays queued so it still stops after.
+fn wait_for_interrupt(tid: i32) -> Result<(), ProcessError> {
+    loop {
+        match wait_for_stop(tid)? {
+            0 => return Ok(()),
+            signal => unsafe { ptrace(libc::PTRACE_CONT, tid, 0, signal as usize) }?,
+        };
+    }
+}
+
+/// wait until <tid> reaches a ptrace stop, returning the signal which stopped it if it was not
+/// stopped by us.
+fn wait_for_stop(tid: i32) -> Result<i32, ProcessError> {
+    loop {
+        let mut status = 0;
+        let res = unsafe { libc::waitpid(tid, &mut status, libc::__WALL) };
+        if res == -1 {
+            let errno = std::io::Error::last_os_error()
+                .raw_os_error()
+                .unwrap_or_default();
+            if errno == libc::EINTR {
+                continue;
+            }
+            return Err(ProcessError::PtraceFailure {
+                request: libc::PTRACE_INTERRUPT,
+                tid,
+                errno,
+            });
+        }
+        if libc::WIFEXITED(status) || libc::WIFSIGNALED(status) {
+            return Err(ProcessError::PtraceFailure {
+                request: libc::PTRACE_INTERRUPT,
+                tid,
+                errno: libc::ESRCH,
+            });
+        }
+        if libc::WIFSTOPPED(status) {
+            let signal = libc::WSTOPSIG(status);
+            // group-stop or PTRACE_INTERRUPT
+            if status >> 16 == libc::PTRACE_EVENT_STOP {
+                return Ok(0);
+            }
+            // signal-delivery-stop, the signal has to be handed back when resuming
+            return Ok(signal);
+        }
+    }
+}
+
+/// reads `/proc/sys/kernel/yama/ptrace_scope`
+fn ptrace_scope() -> Option<u8> {
+    std::fs::read_to_string("/proc/sys/kernel/yama/ptrace_scope")
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use crate::structures::process::{Process, ProcessError};
+
+    fn thread_state(pid: u32) -> char {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap();
+        // the state follows the (comm) field
+        stat.rsplit_once(')')
+            .unwrap()
+            .1
+            .trim()
+            .chars()
+            .next()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_ptrace_session() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let proc = Process::find_pid(child.id()).unwrap();
+        {
+            let mut session = proc.ptrace().unwrap();
+            assert!(session.is_stopped());
+            assert_eq!(session.threads().count(), 1);
+            assert_eq!(thread_state(child.id()), 't');
+
+            session.resume().unwrap();
+            assert!(!session.is_stopped());
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            assert_ne!(thread_state(child.id()), 't');
+
+            session.stop().unwrap();
+            assert_eq!(thread_state(child.id()), 't');
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert_ne!(thread_state(child.id()), 't');
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_stop_after_signal() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let proc = Process::find_pid(child.id()).unwrap();
+        {
+            let mut session = proc.ptrace().unwrap();
+            session.resume().unwrap();
+            // the child sits in a signal-delivery-stop which nobody waited for yet
+            unsafe { libc::kill(child.id() as i32, libc::SIGWINCH) };
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            assert_eq!(thread_state(child.id()), 't');
+
+            session.stop().unwrap();
+            session.resume().unwrap();
+            // the queued interrupt must not stop the child again behind our back
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            assert_ne!(thread_state(child.id()), 't');
+
+            assert!(matches!(
+                unsafe { session.syscall(libc::SYS_getpid, &[0; 7]) },
+                Err(ProcessError::TooManySyscallArgs(7))
+            ));
+        }
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_pending_signals() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let proc = Process::find_pid(child.id()).unwrap();
+        let mut session = proc.ptrace().unwrap();
+        session.stop().unwrap();
+        // as if both arrived while running a syscall
+        session.threads[0].pending_signals = vec![libc::SIGWINCH, libc::SIGTERM];
+        drop(session);
+        assert_eq!(child.wait().unwrap().signal(), Some(libc::SIGTERM));
+    }
+}
@@ -1,6 +1,4 @@
 /// for external usage
-#[feature(external)]
 pub mod external;
 /// for internal usage
-#[feature(internal)]
 pub mod internal;
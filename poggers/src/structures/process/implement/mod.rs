@@ -17,32 +17,41 @@ pub use win32::*;
 
 #[cfg(test)]
 mod test {
+    /// the rw-test processes share a name, so tests using them can't run in parallel
+    static TEST_PROCESS_LOCK: Mutex<()> = Mutex::new(());
     fn spawn_test_process() -> std::process::Child {
         use std::process::Command;
         #[cfg(windows)]
-        let proc = Command::new("./target/release/rw-test.exe")
-            .stdout(Stdio::piped())
-            .spawn()
-            .unwrap();
+        let proc = Command::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../target/release/rw-test.exe"
+        ))
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
         #[cfg(unix)]
-        let proc = Command::new("./target/release/rw-test")
-            .stdout(Stdio::piped())
-            .spawn()
-            .unwrap();
+        let proc = Command::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../target/release/rw-test"
+        ))
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
         proc
     }
     use std::{
         io::{BufRead, BufReader},
         process::Stdio,
+        sync::Mutex,
     };
 
-    use crate::{
-        structures::process::{Proc, Process},
-        traits::Mem,
-    };
+    #[cfg(target_os = "macos")]
+    use crate::structures::process::Proc;
+    use crate::{structures::process::Process, traits::Mem};
 
     #[test]
     fn test_reading() {
+        let _lock = TEST_PROCESS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         let mut proc = spawn_test_process();
         let mut reader = BufReader::new(proc.stdout.take().unwrap());
         let mut bufr = String::new();
@@ -71,19 +80,23 @@ mod test {
         assert_eq!(val, bufr.trim().parse().unwrap());
 
         proc.kill().unwrap();
+        proc.wait().unwrap();
     }
     #[test]
     fn test_name_lookup() {
+        let _lock = TEST_PROCESS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         let mut proc = spawn_test_process();
         let mut reader = BufReader::new(proc.stdout.take().unwrap());
         let mut bufr = String::new();
+
+        // wait for the first line so the process has definitely been exec'd
+        reader.read_line(&mut bufr).ok();
+
         #[cfg(windows)]
         let ex = Process::find_name("rw-test.exe").unwrap();
         #[cfg(unix)]
         let ex = Process::find_by_name("rw-test").unwrap();
 
-        reader.read_line(&mut bufr).ok();
-
         let addr = usize::from_str_radix(bufr.trim_start_matches("0x").trim(), 16).unwrap();
 
         let val = unsafe { ex.read::<u32>(addr).unwrap() };
@@ -95,9 +108,11 @@ mod test {
         assert_eq!(val, bufr.trim().parse().unwrap());
 
         proc.kill().unwrap();
+        proc.wait().unwrap();
     }
     #[test]
     fn test_writing() {
+        let _lock = TEST_PROCESS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         let mut proc = spawn_test_process();
         let mut reader = BufReader::new(proc.stdout.take().unwrap());
         let mut bufr = String::new();
@@ -117,5 +132,6 @@ mod test {
         assert_eq!(4141656, bufr.trim().parse().unwrap());
 
         proc.kill().unwrap();
+        proc.wait().unwrap();
     }
 }
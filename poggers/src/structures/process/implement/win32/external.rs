@@ -5,14 +5,14 @@ use windows::core::PWSTR;
 
 use windows::Win32::System::Threading::{QueryFullProcessImageNameW, PROCESS_NAME_WIN32};
 use windows::Win32::{
-    Foundation::HANDLE,
+    Foundation::{BOOL, HANDLE},
     System::{
         Diagnostics::Debug::{ReadProcessMemory, WriteProcessMemory},
         Memory::{
             VirtualAllocEx, VirtualFreeEx, VirtualProtectEx, VirtualQueryEx,
             MEMORY_BASIC_INFORMATION, MEM_COMMIT, MEM_RELEASE, MEM_RESERVE,
         },
-        Threading::{OpenProcess, PROCESS_ALL_ACCESS},
+        Threading::{IsWow64Process, OpenProcess, PROCESS_ALL_ACCESS},
     },
 };
 
@@ -23,14 +23,22 @@ use crate::{
         modules::{Module, ModuleError},
         process::{External, Process, ProcessError, U32OrString},
         protections::Protections,
+        region::{implement::walk_regions, MemoryRegion},
     },
-    traits::{Mem, MemError},
+    traits::{Mem, MemError, PointerWidth},
 };
 
 use super::super::utils::ProcessUtils;
 use super::WIN_PAGE_SIZE;
 
 impl Mem for Process<External> {
+    fn pointer_width(&self) -> PointerWidth {
+        let mut wow64 = BOOL(0);
+        match unsafe { IsWow64Process(HANDLE(self.handl), &mut wow64) } {
+            Ok(()) if wow64.as_bool() => PointerWidth::Bits32,
+            _ => PointerWidth::NATIVE,
+        }
+    }
     unsafe fn raw_query(&self, addr: usize) -> MEMORY_BASIC_INFORMATION {
         let mut info = MEMORY_BASIC_INFORMATION {
             RegionSize: WIN_PAGE_SIZE,
@@ -44,6 +52,19 @@ impl Mem for Process<External> {
         );
         info
     }
+    fn regions(&self) -> Result<impl Iterator<Item = MemoryRegion>, MemError> {
+        let regions = walk_regions(HANDLE(self.handl), |addr| unsafe {
+            let mut info = MEMORY_BASIC_INFORMATION::default();
+            let written = VirtualQueryEx(
+                HANDLE(self.handl),
+                Some(addr as *const c_void),
+                &mut info,
+                size_of::<MEMORY_BASIC_INFORMATION>(),
+            );
+            (written != 0).then_some(info)
+        });
+        Ok(regions.into_iter())
+    }
     unsafe fn alter_protection(
         &self,
         addr: usize,
@@ -27,6 +27,7 @@ use crate::{
         modules::{Module, ModuleError},
         process::{Internal, Process},
         protections::Protections,
+        region::{implement::walk_regions, MemoryRegion},
     },
     traits::{Mem, MemError},
 };
@@ -46,6 +47,18 @@ impl Mem for Process<Internal> {
         );
         info
     }
+    fn regions(&self) -> Result<impl Iterator<Item = MemoryRegion>, MemError> {
+        let regions = walk_regions(HANDLE(self.handl), |addr| unsafe {
+            let mut info = MEMORY_BASIC_INFORMATION::default();
+            let written = VirtualQuery(
+                Some(addr as *const c_void),
+                &mut info,
+                size_of::<MEMORY_BASIC_INFORMATION>(),
+            );
+            (written != 0).then_some(info)
+        });
+        Ok(regions.into_iter())
+    }
 
     unsafe fn alter_protection(
         &self,
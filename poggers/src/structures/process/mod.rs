@@ -21,10 +21,16 @@ pub struct Process<T = Holding> {
     /// always none on linux, some on windows. is the handle. (to get actual HANDLE, you must wrap
     /// in HANDLE)
     handl: isize,
+    #[cfg(target_os = "linux")]
+    /// how writes are performed, only used by external processes
+    pub(crate) write_method: implement::external::WriteMethod,
+    #[cfg(target_os = "linux")]
+    /// the pointer width of the process, detected when it is opened
+    pub(crate) pointer_width: crate::traits::PointerWidth,
     pub(crate) mrk: PhantomData<T>,
 }
 
-use crate::sigscan::SigScan;
+use crate::{sigscan::SigScan, traits::SafeMem};
 
 /// process errors
 #[derive(Debug, thiserror::Error)]
@@ -39,6 +45,23 @@ pub enum ProcessError {
     /// to be checked outside of macos.
     #[error("unable to get task, are you running as root?")]
     UnableToGetTask,
+    /// ptrace was denied for the process, either by yama's ptrace_scope (the second field) or
+    /// because CAP_SYS_PTRACE is missing.
+    #[error("ptrace denied for {0} (ptrace_scope = {1:?}), are you running as root or with CAP_SYS_PTRACE?")]
+    PtraceDenied(u32, Option<u8>),
+    /// a ptrace request failed
+    #[error("ptrace request {request:#X} on {tid} failed: {}", std::io::Error::from_raw_os_error(*.errno))]
+    PtraceFailure {
+        /// the ptrace request
+        request: u32,
+        /// the thread the request was made on
+        tid: i32,
+        /// the errno reported by the OS
+        errno: i32,
+    },
+    /// a syscall was given more than the 6 arguments syscalls can take
+    #[error("syscalls take at most 6 arguments, got {0}")]
+    TooManySyscallArgs(usize),
 }
 /// Either a u32 or a string
 #[derive(Debug)]
@@ -91,6 +114,13 @@ impl TryFrom<&str> for Process<External> {
     }
 }
 impl SigScan for Process<External> {}
+// reads and writes go through the OS, invalid addresses fail instead of faulting
+unsafe impl SafeMem for Process<External> {
+    fn is_this_process(&self) -> bool {
+        self.pid == std::process::id()
+    }
+}
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
 trait Proc {
     fn find_by_name(name: &str) -> Result<Process<External>, ProcessError>;
     fn find_by_pid(pid: u32) -> Result<Process<External>, ProcessError>;
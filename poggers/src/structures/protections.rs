@@ -1,5 +1,5 @@
 /// Memory Protection Flags
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg(windows)]
 pub enum Protections {
     /// If memory can execute in this page ?
@@ -29,14 +29,18 @@ pub enum Protections {
 #[cfg(unix)]
 #[bitfield_struct::bitfield(u8)]
 pub struct Protections {
-    read: bool,
-    write: bool,
-    execute: bool,
-    none: bool,
+    /// memory can be read
+    pub read: bool,
+    /// memory can be written to
+    pub write: bool,
+    /// memory can be executed
+    pub execute: bool,
+    /// no access
+    pub none: bool,
     #[bits(4)]
     __: u8,
 }
-use std::fmt::{Debug, Display};
+use std::fmt::Display;
 #[cfg(windows)]
 use windows::Win32::System::Memory::PAGE_PROTECTION_FLAGS;
 
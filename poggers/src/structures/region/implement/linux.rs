@@ -0,0 +1,92 @@
+use std::{path::Path, sync::Arc};
+
+use tracing::instrument;
+
+use crate::{
+    structures::{protections::Protections, region::MemoryRegion},
+    traits::MemError,
+};
+
+/// reads and parses a maps file, e.g. `/proc/self/maps` or `/proc/<pid>/maps`
+#[instrument]
+pub(crate) fn read_maps(path: &str) -> Result<Vec<MemoryRegion>, MemError> {
+    let maps = std::fs::read_to_string(path).map_err(MemError::QueryFailure)?;
+    Ok(maps.lines().filter_map(parse_maps_line).collect())
+}
+
+/// parses a single line of a maps file
+/// `address           perms offset  dev   inode   pathname`
+/// `00400000-00452000 r-xp 00000000 08:02 173521  /usr/bin/dbus-daemon`
+pub(crate) fn parse_maps_line(line: &str) -> Option<MemoryRegion> {
+    let mut parts = line.splitn(6, ' ');
+    let (start, end) = parts.next()?.split_once('-')?;
+    let perms = parts.next()?.as_bytes();
+    let offset = parts.next()?;
+    let _dev = parts.next()?;
+    let _inode = parts.next()?;
+    let path = parts.next().map(str::trim).filter(|x| !x.is_empty());
+
+    if perms.len() < 4 {
+        return None;
+    }
+    let mut protections = Protections::new()
+        .with_read(perms[0] == b'r')
+        .with_write(perms[1] == b'w')
+        .with_execute(perms[2] == b'x');
+    if !protections.read() && !protections.write() && !protections.execute() {
+        protections.set_none(true);
+    }
+
+    Some(MemoryRegion {
+        start: usize::from_str_radix(start, 16).ok()?,
+        end: usize::from_str_radix(end, 16).ok()?,
+        protections,
+        shared: perms[3] == b's',
+        offset: usize::from_str_radix(offset, 16).ok()?,
+        path: path.map(|x| Arc::from(Path::new(x))),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::parse_maps_line;
+    use crate::{structures::process::Process, traits::Mem};
+
+    #[test]
+    fn test_parse_maps_line() {
+        let region = parse_maps_line(
+            "00400000-00452000 r-xp 00001000 08:02 173521      /usr/bin/dbus-daemon",
+        )
+        .unwrap();
+        assert_eq!(region.get_start(), 0x400000);
+        assert_eq!(region.get_end(), 0x452000);
+        assert_eq!(region.get_offset(), 0x1000);
+        assert!(region.get_protections().read());
+        assert!(!region.get_protections().write());
+        assert!(region.get_protections().execute());
+        assert!(region.is_private());
+        assert_eq!(region.get_path(), Some(Path::new("/usr/bin/dbus-daemon")));
+        assert!(region.is_file_backed());
+
+        let region = parse_maps_line("7f0000000000-7f0000021000 ---s 00000000 00:00 0 ").unwrap();
+        assert!(region.get_protections().none());
+        assert!(region.is_shared());
+        assert_eq!(region.get_path(), None);
+        assert!(!region.is_file_backed());
+
+        let region = parse_maps_line("01e4f000-01e70000 rw-p 00000000 00:00 0   [heap]").unwrap();
+        assert_eq!(region.get_path(), Some(Path::new("[heap]")));
+        assert!(!region.is_file_backed());
+    }
+    #[test]
+    fn test_query_this_process() {
+        static VALUE: u32 = 0x1337;
+        let proc = Process::this_process();
+        let region = proc.query(&VALUE as *const u32 as usize).unwrap();
+        assert!(region.contains(&VALUE as *const u32 as usize));
+        assert!(region.get_protections().read());
+        assert!(proc.regions().unwrap().count() > 0);
+    }
+}
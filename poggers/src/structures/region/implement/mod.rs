@@ -0,0 +1,11 @@
+#[cfg(target_os = "linux")]
+mod linux;
+
+#[cfg(target_os = "linux")]
+pub(crate) use linux::*;
+
+#[cfg(windows)]
+mod win32;
+
+#[cfg(windows)]
+pub(crate) use win32::*;
@@ -0,0 +1,65 @@
+use std::{ffi::OsString, os::windows::ffi::OsStringExt, path::Path, sync::Arc};
+
+use windows::Win32::{
+    Foundation::{HANDLE, MAX_PATH},
+    System::{
+        Memory::{MEMORY_BASIC_INFORMATION, MEM_COMMIT, MEM_IMAGE, MEM_MAPPED},
+        ProcessStatus::GetMappedFileNameW,
+    },
+};
+
+use crate::structures::{protections::Protections, region::MemoryRegion};
+
+/// walks the address space using <query> (VirtualQuery / VirtualQueryEx), collecting every committed region.
+/// <query> should return None once it fails to query an address.
+/// image and mapped file regions get the path of their file from <process>.
+pub(crate) fn walk_regions(
+    process: HANDLE,
+    mut query: impl FnMut(usize) -> Option<MEMORY_BASIC_INFORMATION>,
+) -> Vec<MemoryRegion> {
+    let mut regions = vec![];
+    let mut addr = 0usize;
+    // the regions of one mapping share an allocation base, so each file is only looked up once
+    let mut mapping: Option<(usize, Option<Arc<Path>>)> = None;
+    while let Some(info) = query(addr) {
+        let start = info.BaseAddress as usize;
+        let Some(end) = start.checked_add(info.RegionSize) else {
+            break;
+        };
+        if info.State == MEM_COMMIT {
+            let base = info.AllocationBase as usize;
+            let path = match &mapping {
+                Some((at, path)) if *at == base => path.clone(),
+                _ if info.Type == MEM_IMAGE || info.Type == MEM_MAPPED => {
+                    let path = mapped_file(process, start);
+                    mapping = Some((base, path.clone()));
+                    path
+                }
+                _ => None,
+            };
+            regions.push(MemoryRegion {
+                start,
+                end,
+                // strip modifiers such as PAGE_GUARD
+                protections: Protections::from(info.Protect.0 & 0xFF),
+                shared: info.Type == MEM_MAPPED,
+                offset: 0,
+                path,
+            });
+        }
+        if end <= addr {
+            break;
+        }
+        addr = end;
+    }
+    regions
+}
+
+/// the file mapped at <addr>, as a device path such as
+/// `\Device\HarddiskVolume3\Windows\System32\kernel32.dll`.
+/// none for anonymous memory and sections backed by the page file.
+fn mapped_file(process: HANDLE, addr: usize) -> Option<Arc<Path>> {
+    let mut name = [0u16; MAX_PATH as usize * 2];
+    let len = unsafe { GetMappedFileNameW(process, addr as *const _, &mut name) } as usize;
+    (len != 0).then(|| Arc::from(Path::new(&OsString::from_wide(&name[..len]))))
+}
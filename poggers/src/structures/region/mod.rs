@@ -0,0 +1,201 @@
+/// OS specific implementations of [MemoryRegion] enumeration
+pub(crate) mod implement;
+
+use std::{ops::Range, path::Path, sync::Arc};
+
+use super::protections::Protections;
+
+/// represents a region of mapped memory in a process
+#[derive(Debug, Clone)]
+pub struct MemoryRegion {
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) protections: Protections,
+    pub(crate) shared: bool,
+    pub(crate) offset: usize,
+    pub(crate) path: Option<Arc<Path>>,
+}
+
+impl MemoryRegion {
+    /// Get the start address of the region
+    pub const fn get_start(&self) -> usize {
+        self.start
+    }
+    /// Get the end address of the region (exclusive)
+    pub const fn get_end(&self) -> usize {
+        self.end
+    }
+    /// Get the size of the region
+    pub const fn get_size(&self) -> usize {
+        self.end - self.start
+    }
+    /// Get the protections of the region
+    pub const fn get_protections(&self) -> &Protections {
+        &self.protections
+    }
+    /// Get the offset into the backing file, always 0 for anonymous regions
+    pub const fn get_offset(&self) -> usize {
+        self.offset
+    }
+    /// Get the path backing this region.
+    /// on linux this may also be a pseudo path such as `[heap]` or `[stack]`, on windows it is the
+    /// device path of the mapped file such as `\Device\HarddiskVolume3\Windows\System32\ntdll.dll`
+    pub fn get_path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+    /// if the region is shared with other processes
+    pub const fn is_shared(&self) -> bool {
+        self.shared
+    }
+    /// if the region is private to this process (copy on write)
+    pub const fn is_private(&self) -> bool {
+        !self.shared
+    }
+    /// if the region can be read from
+    pub fn is_readable(&self) -> bool {
+        #[cfg(unix)]
+        return self.protections.read();
+        #[cfg(windows)]
+        return matches!(
+            self.protections,
+            Protections::ExecuteRead
+                | Protections::ExecuteReadWrite
+                | Protections::ExecuteWriteCopy
+                | Protections::ReadOnly
+                | Protections::ReadWrite
+                | Protections::WriteCopy
+        );
+    }
+    /// if the region can be written to
+    pub fn is_writable(&self) -> bool {
+        #[cfg(unix)]
+        return self.protections.write();
+        #[cfg(windows)]
+        return matches!(
+            self.protections,
+            Protections::ExecuteReadWrite
+                | Protections::ExecuteWriteCopy
+                | Protections::ReadWrite
+                | Protections::WriteCopy
+        );
+    }
+    /// if the region can be executed
+    pub fn is_executable(&self) -> bool {
+        #[cfg(unix)]
+        return self.protections.execute();
+        #[cfg(windows)]
+        return matches!(
+            self.protections,
+            Protections::Execute
+                | Protections::ExecuteRead
+                | Protections::ExecuteReadWrite
+                | Protections::ExecuteWriteCopy
+        );
+    }
+    /// if the region is backed by a file, rather than anonymous memory such as the heap, the
+    /// stack or JIT code. pseudo paths like `[heap]` don't count as files.
+    /// on windows these are the MEM_IMAGE and MEM_MAPPED regions of a file.
+    pub fn is_file_backed(&self) -> bool {
+        self.get_path()
+            .is_some_and(|path| !path.as_os_str().to_string_lossy().starts_with('['))
+    }
+    /// if <addr> lies within this region
+    pub const fn contains(&self, addr: usize) -> bool {
+        self.start <= addr && addr < self.end
+    }
+}
+
+/// a filter picking which regions of a process to look at, every condition left unset matches
+/// any region.
+/// ```rs
+/// // jit code which belongs to no module
+/// let filter = RegionFilter::new().with_executable(true).with_file_backed(false);
+/// let found = unsafe { process.scan_process_filtered("55 48 8B EC", filter)? };
+/// ```
+#[derive(Debug, Clone)]
+pub struct RegionFilter {
+    pub(crate) executable: Option<bool>,
+    pub(crate) writable: Option<bool>,
+    pub(crate) private: Option<bool>,
+    pub(crate) file_backed: Option<bool>,
+    pub(crate) range: (usize, usize),
+    pub(crate) modules: Option<Vec<String>>,
+}
+
+impl Default for RegionFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RegionFilter {
+    /// a filter matching every region
+    pub fn new() -> Self {
+        Self {
+            executable: None,
+            writable: None,
+            private: None,
+            file_backed: None,
+            range: (0, usize::MAX),
+            modules: None,
+        }
+    }
+    /// only match regions which are, or with false aren't, executable
+    pub fn with_executable(mut self, executable: bool) -> Self {
+        self.executable = Some(executable);
+        self
+    }
+    /// only match regions which are, or with false aren't, writable
+    pub fn with_writable(mut self, writable: bool) -> Self {
+        self.writable = Some(writable);
+        self
+    }
+    /// only match private (copy on write) regions, or with false shared ones
+    pub fn with_private(mut self, private: bool) -> Self {
+        self.private = Some(private);
+        self
+    }
+    /// only match regions backed by a file, or with false anonymous ones.
+    /// see [MemoryRegion::is_file_backed]
+    pub fn with_file_backed(mut self, file_backed: bool) -> Self {
+        self.file_backed = Some(file_backed);
+        self
+    }
+    /// only match memory between <start> and <end>, regions crossing the bounds are cut down so
+    /// scans only find matches lying entirely within them
+    pub fn with_range(mut self, start: usize, end: usize) -> Self {
+        self.range = (start, end);
+        self
+    }
+    /// only match regions mapped from a module with <name>, either its file name or full path.
+    /// full paths on windows are device paths, see [MemoryRegion::get_path].
+    /// can be called multiple times to match any of several modules.
+    pub fn with_module(mut self, name: &str) -> Self {
+        self.modules
+            .get_or_insert_with(Vec::new)
+            .push(name.to_string());
+        self
+    }
+    /// if <region> passes every condition of the filter, ignoring the range
+    pub fn matches(&self, region: &MemoryRegion) -> bool {
+        let is = |wanted: Option<bool>, actual: bool| wanted.is_none_or(|x| x == actual);
+        is(self.executable, region.is_executable())
+            && is(self.writable, region.is_writable())
+            && is(self.private, region.is_private())
+            && is(self.file_backed, region.is_file_backed())
+            && self.modules.as_ref().is_none_or(|modules| {
+                region.get_path().is_some_and(|path| {
+                    modules.iter().any(|name| {
+                        path.as_os_str() == name.as_str()
+                            || path.file_name().is_some_and(|x| x == name.as_str())
+                    })
+                })
+            })
+    }
+    /// the part of <region> the filter matches, none if it doesn't match at all
+    pub fn clamp(&self, region: &MemoryRegion) -> Option<Range<usize>> {
+        let start = region.get_start().max(self.range.0);
+        let end = region.get_end().min(self.range.1);
+        (start < end && self.matches(region)).then_some(start..end)
+    }
+}
@@ -0,0 +1,466 @@
+use std::{
+    io::{Read, Write},
+    ops::Range,
+    path::Path,
+    sync::Arc,
+};
+
+use crate::{
+    structures::{protections::Protections, region::MemoryRegion},
+    traits::{Mem, MemError},
+};
+
+const MAGIC: &[u8; 8] = b"PGSNAP\x01\x00";
+/// saved data is split into pages of this size, pages which are all zero only take one byte
+const SAVE_PAGE_SIZE: usize = 0x1000;
+/// the largest region which can be saved and loaded, so a corrupt file can't ask for huge
+/// allocations
+const MAX_REGION_SIZE: usize = u32::MAX as usize;
+/// the longest region path which can be saved and loaded
+const MAX_PATH_LEN: usize = 0x1000;
+
+/// a copy of the readable memory of a process at one point in time
+/// ```rs
+/// let before = Snapshot::capture(&process)?;
+/// // do something in game
+/// let after = Snapshot::capture(&process)?;
+/// for change in before.diff(&after) { .. }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    pub(crate) regions: Vec<SnapshotRegion>,
+}
+
+/// a region of a [Snapshot] and its contents
+#[derive(Debug, Clone)]
+pub struct SnapshotRegion {
+    pub(crate) region: MemoryRegion,
+    pub(crate) data: Vec<u8>,
+}
+
+/// a difference between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotChange {
+    /// memory which is only in the newer snapshot
+    Mapped(Range<usize>),
+    /// memory which is only in the older snapshot
+    Unmapped(Range<usize>),
+    /// memory in both snapshots with different contents
+    Changed(Range<usize>),
+}
+
+impl SnapshotChange {
+    /// get the range of memory which is affected
+    pub fn range(&self) -> &Range<usize> {
+        match self {
+            SnapshotChange::Mapped(range)
+            | SnapshotChange::Unmapped(range)
+            | SnapshotChange::Changed(range) => range,
+        }
+    }
+}
+
+impl SnapshotRegion {
+    /// get the region, its bounds may be narrower than the mapping when captured with
+    /// [Snapshot::capture_range]
+    pub fn get_region(&self) -> &MemoryRegion {
+        &self.region
+    }
+    /// get the contents of the region
+    pub fn get_data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl Snapshot {
+    /// capture every readable region of <process>, regions which fail to read are skipped
+    /// # Safety
+    /// reads every readable region of the process.
+    pub unsafe fn capture<T: Mem>(process: &T) -> Result<Self, MemError> {
+        Self::capture_range(process, 0..usize::MAX)
+    }
+    /// capture the readable memory of <process> which lies within <range>
+    /// # Safety
+    /// reads every readable region of the process within the range.
+    pub unsafe fn capture_range<T: Mem>(
+        process: &T,
+        range: Range<usize>,
+    ) -> Result<Self, MemError> {
+        let mut regions = vec![];
+        for mut region in process.regions()? {
+            if !region.is_readable() {
+                continue;
+            }
+            region.start = region.start.max(range.start);
+            region.end = region.end.min(range.end);
+            if region.start >= region.end {
+                continue;
+            }
+            let Ok(data) = process.read_sized(region.start, region.get_size()) else {
+                continue;
+            };
+            regions.push(SnapshotRegion { region, data });
+        }
+        Ok(Self { regions })
+    }
+    /// get the captured regions, sorted by address
+    pub fn regions(&self) -> &[SnapshotRegion] {
+        &self.regions
+    }
+    /// the amount of bytes captured
+    pub fn size(&self) -> usize {
+        self.regions.iter().map(|region| region.data.len()).sum()
+    }
+    /// get the captured bytes at <addr>, if all <size> of them were captured in the same region
+    pub fn read(&self, addr: usize, size: usize) -> Option<&[u8]> {
+        let region = self.region_at(addr)?;
+        let offset = addr - region.region.start;
+        region.data.get(offset..offset.checked_add(size)?)
+    }
+    fn region_at(&self, addr: usize) -> Option<&SnapshotRegion> {
+        let idx = self
+            .regions
+            .partition_point(|region| region.region.end <= addr);
+        self.regions
+            .get(idx)
+            .filter(|region| region.region.start <= addr)
+    }
+    /// compare with a <newer> snapshot, returning every range which was mapped, unmapped or
+    /// changed sorted by address
+    pub fn diff(&self, newer: &Snapshot) -> Vec<SnapshotChange> {
+        let mut changes = vec![];
+        for (range, old, new) in overlaps(&self.regions, &newer.regions) {
+            match (old, new) {
+                (Some(old), Some(new)) => changed_ranges(range.start, old, new, &mut changes),
+                (Some(_), None) => push_merged(&mut changes, SnapshotChange::Unmapped(range)),
+                (None, Some(_)) => push_merged(&mut changes, SnapshotChange::Mapped(range)),
+                (None, None) => {}
+            }
+        }
+        changes
+    }
+    /// write the snapshot to <writer>. pages which are all zero are stored as a single byte.
+    /// regions larger than 4 GiB can't be saved.
+    pub fn write_to(&self, mut writer: impl Write) -> std::io::Result<()> {
+        if self.regions.iter().any(|x| x.data.len() > MAX_REGION_SIZE) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "region too large to save",
+            ));
+        }
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(self.regions.len() as u64).to_le_bytes())?;
+        for SnapshotRegion { region, data } in &self.regions {
+            writer.write_all(&(region.start as u64).to_le_bytes())?;
+            writer.write_all(&(region.end as u64).to_le_bytes())?;
+            writer.write_all(&protections_to_u32(&region.protections).to_le_bytes())?;
+            writer.write_all(&[region.shared as u8])?;
+            writer.write_all(&(region.offset as u64).to_le_bytes())?;
+            match region.get_path() {
+                Some(path) if path.as_os_str().len() <= MAX_PATH_LEN => {
+                    let path = path.to_string_lossy();
+                    writer.write_all(&(path.len() as u32).to_le_bytes())?;
+                    writer.write_all(path.as_bytes())?;
+                }
+                _ => writer.write_all(&u32::MAX.to_le_bytes())?,
+            }
+            for page in data.chunks(SAVE_PAGE_SIZE) {
+                if page.iter().all(|x| *x == 0) {
+                    writer.write_all(&[0])?;
+                } else {
+                    writer.write_all(&[1])?;
+                    writer.write_all(page)?;
+                }
+            }
+        }
+        Ok(())
+    }
+    /// read a snapshot written by [Snapshot::write_to]
+    pub fn read_from(mut reader: impl Read) -> std::io::Result<Self> {
+        let invalid = |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a poggers snapshot"));
+        }
+        let count = read_u64(&mut reader)?;
+        let mut regions = vec![];
+        for _ in 0..count {
+            let start = read_u64(&mut reader)? as usize;
+            let end = read_u64(&mut reader)? as usize;
+            let mut protections = [0u8; 4];
+            reader.read_exact(&mut protections)?;
+            let mut shared = [0u8];
+            reader.read_exact(&mut shared)?;
+            let offset = read_u64(&mut reader)? as usize;
+            let mut path_len = [0u8; 4];
+            reader.read_exact(&mut path_len)?;
+            let path = match u32::from_le_bytes(path_len) {
+                u32::MAX => None,
+                len if len as usize > MAX_PATH_LEN => return Err(invalid("path too long")),
+                len => {
+                    let mut path = vec![];
+                    read_exactly(&mut reader, &mut path, len as usize)?;
+                    let path = String::from_utf8(path).map_err(|_| invalid("invalid path"))?;
+                    Some(Arc::from(Path::new(&path)))
+                }
+            };
+            let size = end
+                .checked_sub(start)
+                .filter(|size| *size <= MAX_REGION_SIZE)
+                .ok_or_else(|| invalid("invalid region bounds"))?;
+            // grown page by page, so a truncated file fails before allocating the whole region
+            let mut data = vec![];
+            while data.len() < size {
+                let page = SAVE_PAGE_SIZE.min(size - data.len());
+                let mut kind = [0u8];
+                reader.read_exact(&mut kind)?;
+                match kind[0] {
+                    0 => data.resize(data.len() + page, 0),
+                    1 => read_exactly(&mut reader, &mut data, page)?,
+                    _ => return Err(invalid("invalid page")),
+                }
+            }
+            regions.push(SnapshotRegion {
+                region: MemoryRegion {
+                    start,
+                    end,
+                    protections: protections_from_u32(u32::from_le_bytes(protections)),
+                    shared: shared[0] != 0,
+                    offset,
+                    path,
+                },
+                data,
+            });
+        }
+        Ok(Self { regions })
+    }
+    /// save the snapshot to the file at <path>
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+    /// load a snapshot saved with [Snapshot::save]
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Self::read_from(std::io::BufReader::new(std::fs::File::open(path)?))
+    }
+}
+
+/// append exactly <len> bytes from <reader> to <buf>, only allocating as much as was read
+fn read_exactly(reader: &mut impl Read, buf: &mut Vec<u8>, len: usize) -> std::io::Result<()> {
+    if reader.take(len as u64).read_to_end(buf)? != len {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+fn read_u64(reader: &mut impl Read) -> std::io::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+#[cfg(unix)]
+fn protections_to_u32(protections: &Protections) -> u32 {
+    protections.native() as u32
+}
+#[cfg(unix)]
+fn protections_from_u32(protections: u32) -> Protections {
+    Protections::from_native(protections as i32)
+}
+#[cfg(windows)]
+fn protections_to_u32(protections: &Protections) -> u32 {
+    protections.u32()
+}
+#[cfg(windows)]
+fn protections_from_u32(protections: u32) -> Protections {
+    Protections::from(protections)
+}
+
+/// a range of memory and its contents in the old and new snapshot
+type Overlap<'a> = (Range<usize>, Option<&'a [u8]>, Option<&'a [u8]>);
+
+/// splits the address space covered by either list of regions into ranges, each with the data
+/// of the old and new region covering it
+fn overlaps<'a>(old: &'a [SnapshotRegion], new: &'a [SnapshotRegion]) -> Vec<Overlap<'a>> {
+    let mut bounds = old
+        .iter()
+        .chain(new.iter())
+        .flat_map(|region| [region.region.start, region.region.end])
+        .collect::<Vec<_>>();
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    let slice = |regions: &'a [SnapshotRegion], range: &Range<usize>| {
+        let idx = regions.partition_point(|region| region.region.end <= range.start);
+        let region = regions
+            .get(idx)
+            .filter(|region| region.region.start <= range.start)?;
+        let offset = range.start - region.region.start;
+        Some(&region.data[offset..offset + range.len()])
+    };
+    bounds
+        .windows(2)
+        .map(|bound| {
+            let range = bound[0]..bound[1];
+            let (old, new) = (slice(old, &range), slice(new, &range));
+            (range, old, new)
+        })
+        .collect()
+}
+
+/// finds the runs of bytes which differ between <old> and <new>
+fn changed_ranges(start: usize, old: &[u8], new: &[u8], changes: &mut Vec<SnapshotChange>) {
+    const BLOCK: usize = 64;
+    // the offset the current run of changed bytes started at
+    let mut run = None;
+    for (block, (old, new)) in old.chunks(BLOCK).zip(new.chunks(BLOCK)).enumerate() {
+        if run.is_none() && old == new {
+            continue;
+        }
+        for (i, (a, b)) in old.iter().zip(new).enumerate() {
+            let at = block * BLOCK + i;
+            match (a != b, run) {
+                (true, None) => run = Some(at),
+                (false, Some(from)) => {
+                    push_merged(changes, SnapshotChange::Changed(start + from..start + at));
+                    run = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    if let Some(from) = run {
+        push_merged(
+            changes,
+            SnapshotChange::Changed(start + from..start + old.len()),
+        );
+    }
+}
+
+/// pushes <change>, extending the last change instead if it is of the same kind and adjacent
+fn push_merged(changes: &mut Vec<SnapshotChange>, change: SnapshotChange) {
+    let extended = match (changes.last_mut(), &change) {
+        (Some(SnapshotChange::Changed(last)), SnapshotChange::Changed(range))
+        | (Some(SnapshotChange::Mapped(last)), SnapshotChange::Mapped(range))
+        | (Some(SnapshotChange::Unmapped(last)), SnapshotChange::Unmapped(range))
+            if last.end == range.start =>
+        {
+            last.end = range.end;
+            true
+        }
+        _ => false,
+    };
+    if !extended {
+        changes.push(change);
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::{changed_ranges, Snapshot, SnapshotChange, MAGIC};
+    use crate::{structures::process::Process, traits::Mem};
+
+    #[test]
+    fn test_snapshot_diff() {
+        let proc = Process::find_pid(std::process::id()).unwrap();
+        unsafe {
+            let pages = libc::mmap(
+                std::ptr::null_mut(),
+                0x4000,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            ) as usize;
+            libc::munmap((pages + 0x3000) as *mut libc::c_void, 0x1000);
+            let range = pages..pages + 0x4000;
+
+            let before = Snapshot::capture_range(&proc, range.clone()).unwrap();
+            assert_eq!(before.size(), 0x3000);
+
+            proc.write_raw(pages + 0x10, &[1, 2]).unwrap();
+            proc.write_raw(pages + 0x1FFF, &[3, 4]).unwrap();
+            libc::munmap((pages + 0x2000) as *mut libc::c_void, 0x1000);
+            libc::mmap(
+                (pages + 0x3000) as *mut libc::c_void,
+                0x1000,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS | libc::MAP_FIXED,
+                -1,
+                0,
+            );
+            let after = Snapshot::capture_range(&proc, range).unwrap();
+            assert_eq!(after.read(pages + 0x10, 2), Some(&[1u8, 2][..]));
+
+            assert_eq!(
+                before.diff(&after),
+                vec![
+                    SnapshotChange::Changed(pages + 0x10..pages + 0x12),
+                    SnapshotChange::Changed(pages + 0x1FFF..pages + 0x2000),
+                    SnapshotChange::Unmapped(pages + 0x2000..pages + 0x3000),
+                    SnapshotChange::Mapped(pages + 0x3000..pages + 0x4000),
+                ]
+            );
+
+            let mut saved = vec![];
+            after.write_to(&mut saved).unwrap();
+            // the last page is all zeroes
+            assert!(saved.len() < 0x2100);
+            let loaded = Snapshot::read_from(&saved[..]).unwrap();
+            assert!(loaded.diff(&after).is_empty());
+            assert_eq!(loaded.regions().len(), after.regions().len());
+
+            libc::munmap(pages as *mut libc::c_void, 0x4000);
+        }
+    }
+
+    #[test]
+    fn test_changed_runs() {
+        let old = vec![0u8; 0x100];
+        let mut new = old.clone();
+        new[0x3E..0x43].fill(1);
+        new[0xFF] = 1;
+        let mut changes = vec![];
+        changed_ranges(0x1000, &old, &new, &mut changes);
+        assert_eq!(
+            changes,
+            vec![
+                SnapshotChange::Changed(0x103E..0x1043),
+                SnapshotChange::Changed(0x10FF..0x1100),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_untrusted() {
+        let header = |end: u64, path_len: u32| {
+            let mut file = MAGIC.to_vec();
+            file.extend(1u64.to_le_bytes());
+            file.extend(0u64.to_le_bytes());
+            file.extend(end.to_le_bytes());
+            file.extend([0; 5]);
+            file.extend(0u64.to_le_bytes());
+            file.extend(path_len.to_le_bytes());
+            file
+        };
+        let kind = |file: &[u8]| Snapshot::read_from(file).err().map(|x| x.kind());
+        assert_eq!(
+            kind(&header(u64::MAX, u32::MAX)),
+            Some(std::io::ErrorKind::InvalidData)
+        );
+        assert_eq!(
+            kind(&header(0x1000, u32::MAX - 1)),
+            Some(std::io::ErrorKind::InvalidData)
+        );
+        assert_eq!(
+            kind(&header(0x1000, 0x10)),
+            Some(std::io::ErrorKind::UnexpectedEof)
+        );
+        // claims a page of data but the file ends
+        let mut file = header(u32::MAX as u64, u32::MAX);
+        file.extend([1, 0xFF]);
+        assert_eq!(kind(&file), Some(std::io::ErrorKind::UnexpectedEof));
+    }
+}
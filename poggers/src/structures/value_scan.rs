@@ -0,0 +1,780 @@
+use crate::traits::{Mem, MemError};
+
+/// the largest block of memory read at once, regions are split into chunks of this size
+const CHUNK_SIZE: usize = 0x100_0000;
+
+/// the type of value a [ValueScanSession] searches for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    /// i8
+    I8,
+    /// i16
+    I16,
+    /// i32
+    I32,
+    /// i64
+    I64,
+    /// u8
+    U8,
+    /// u16
+    U16,
+    /// u32
+    U32,
+    /// u64
+    U64,
+    /// f32, compared with the session's tolerance
+    F32,
+    /// f64, compared with the session's tolerance
+    F64,
+    /// an array of bytes of the given length
+    Bytes(usize),
+}
+
+/// a value of a [ValueKind]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// i8
+    I8(i8),
+    /// i16
+    I16(i16),
+    /// i32
+    I32(i32),
+    /// i64
+    I64(i64),
+    /// u8
+    U8(u8),
+    /// u16
+    U16(u16),
+    /// u32
+    U32(u32),
+    /// u64
+    U64(u64),
+    /// f32
+    F32(f32),
+    /// f64
+    F64(f64),
+    /// an array of bytes
+    Bytes(Vec<u8>),
+}
+
+/// how candidates are filtered by a scan
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanFilter {
+    /// the value is unknown, keeps everything. only valid for the first scan
+    Unknown,
+    /// the value equals this value
+    Exact(Value),
+    /// the value lies within this range (inclusive)
+    Between(Value, Value),
+    /// the value differs from the previous scan
+    Changed,
+    /// the value is the same as in the previous scan
+    Unchanged,
+    /// the value is larger than in the previous scan
+    Increased,
+    /// the value is smaller than in the previous scan
+    Decreased,
+    /// the value grew by exactly this much since the previous scan
+    IncreasedBy(Value),
+    /// the value shrunk by exactly this much since the previous scan
+    DecreasedBy(Value),
+}
+
+/// Value scan failures
+#[derive(Debug, thiserror::Error)]
+pub enum ValueScanError {
+    /// Unable to query the regions of the process
+    #[error("{0}")]
+    MemError(#[from] MemError),
+    /// next_scan was called before first_scan
+    #[error("no first scan has been done")]
+    NoFirstScan,
+    /// the filter compares against a previous scan, which the first scan doesn't have
+    #[error("{0:?} needs a previous scan")]
+    NeedsPreviousScan(ScanFilter),
+    /// the filter can't be used with the kind of value being scanned for
+    #[error("{0:?} can not be used when scanning for {1:?}")]
+    UnsupportedFilter(ScanFilter, ValueKind),
+    /// a value in the filter is of a different kind than the one being scanned for
+    #[error("{0:?} is not a {1:?}")]
+    KindMismatch(Value, ValueKind),
+}
+
+/// a first-scan/next-scan value search over the writable memory of a process.
+/// candidates are stored per chunk of memory, as a copy of the chunk with a bitmap of the
+/// candidate offsets while most of the chunk matches, and as a list of 32 bit offsets with their
+/// previous values once narrowed down.
+/// ```rs
+/// let mut session = ValueScanSession::new(&process, ValueKind::I32);
+/// session.first_scan(ScanFilter::Exact(Value::I32(100)))?;
+/// // take some damage
+/// session.next_scan(ScanFilter::Decreased)?;
+/// for (addr, value) in session.results() { .. }
+/// ```
+#[derive(Debug)]
+pub struct ValueScanSession<'a, T: Mem> {
+    process: &'a T,
+    kind: ValueKind,
+    alignment: usize,
+    tolerance: f64,
+    range: (usize, usize),
+    chunks: Option<Vec<Chunk>>,
+}
+
+#[derive(Debug)]
+struct Chunk {
+    start: usize,
+    candidates: Candidates,
+}
+
+#[derive(Debug)]
+enum Candidates {
+    /// a copy of the chunk and a bit per offset which is a candidate, none if every aligned
+    /// offset is one
+    Dense {
+        data: Vec<u8>,
+        hits: Option<Vec<u64>>,
+    },
+    /// offsets into the chunk and the value at each offset, packed
+    Sparse {
+        offsets: Vec<u32>,
+        previous: Vec<u8>,
+    },
+}
+
+/// a decoded value used for comparisons
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+enum Number {
+    Int(i128),
+    Float(f64),
+}
+
+impl Chunk {
+    /// store the values at <offsets> into <data> as candidates, in whichever form takes less
+    /// memory. none if there are no candidates
+    fn pack(start: usize, data: Vec<u8>, offsets: Vec<u32>, size: usize) -> Option<Self> {
+        if offsets.is_empty() {
+            return None;
+        }
+        let sparse_size = offsets.len() * (std::mem::size_of::<u32>() + size);
+        let candidates = if sparse_size > data.len() + data.len() / 8 {
+            let mut hits = vec![0u64; data.len().div_ceil(64)];
+            for offset in offsets {
+                hits[offset as usize / 64] |= 1 << (offset % 64);
+            }
+            Candidates::Dense {
+                data,
+                hits: Some(hits),
+            }
+        } else {
+            let previous = offsets
+                .iter()
+                .flat_map(|x| &data[*x as usize..*x as usize + size])
+                .copied()
+                .collect();
+            Candidates::Sparse { offsets, previous }
+        };
+        Some(Self { start, candidates })
+    }
+}
+
+impl ValueKind {
+    /// the size of the value in bytes
+    pub const fn size(&self) -> usize {
+        match self {
+            ValueKind::I8 | ValueKind::U8 => 1,
+            ValueKind::I16 | ValueKind::U16 => 2,
+            ValueKind::I32 | ValueKind::U32 | ValueKind::F32 => 4,
+            ValueKind::I64 | ValueKind::U64 | ValueKind::F64 => 8,
+            ValueKind::Bytes(len) => *len,
+        }
+    }
+    /// decode a value of this kind from <bytes>, which must be [ValueKind::size] long
+    pub fn decode(&self, bytes: &[u8]) -> Value {
+        macro_rules! decode {
+            ($variant:ident, $ty:ty) => {
+                Value::$variant(<$ty>::from_ne_bytes(bytes.try_into().unwrap()))
+            };
+        }
+        match self {
+            ValueKind::I8 => decode!(I8, i8),
+            ValueKind::I16 => decode!(I16, i16),
+            ValueKind::I32 => decode!(I32, i32),
+            ValueKind::I64 => decode!(I64, i64),
+            ValueKind::U8 => decode!(U8, u8),
+            ValueKind::U16 => decode!(U16, u16),
+            ValueKind::U32 => decode!(U32, u32),
+            ValueKind::U64 => decode!(U64, u64),
+            ValueKind::F32 => decode!(F32, f32),
+            ValueKind::F64 => decode!(F64, f64),
+            ValueKind::Bytes(_) => Value::Bytes(bytes.to_vec()),
+        }
+    }
+    fn number(&self, bytes: &[u8]) -> Option<Number> {
+        Some(match self.decode(bytes) {
+            Value::F32(x) => Number::Float(x as f64),
+            Value::F64(x) => Number::Float(x),
+            Value::Bytes(_) => return None,
+            value => Number::Int(value.as_int()?),
+        })
+    }
+}
+
+impl Value {
+    /// get the kind of this value
+    pub fn kind(&self) -> ValueKind {
+        match self {
+            Value::I8(_) => ValueKind::I8,
+            Value::I16(_) => ValueKind::I16,
+            Value::I32(_) => ValueKind::I32,
+            Value::I64(_) => ValueKind::I64,
+            Value::U8(_) => ValueKind::U8,
+            Value::U16(_) => ValueKind::U16,
+            Value::U32(_) => ValueKind::U32,
+            Value::U64(_) => ValueKind::U64,
+            Value::F32(_) => ValueKind::F32,
+            Value::F64(_) => ValueKind::F64,
+            Value::Bytes(x) => ValueKind::Bytes(x.len()),
+        }
+    }
+    /// get the in memory representation of this value
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Value::I8(x) => x.to_ne_bytes().to_vec(),
+            Value::I16(x) => x.to_ne_bytes().to_vec(),
+            Value::I32(x) => x.to_ne_bytes().to_vec(),
+            Value::I64(x) => x.to_ne_bytes().to_vec(),
+            Value::U8(x) => x.to_ne_bytes().to_vec(),
+            Value::U16(x) => x.to_ne_bytes().to_vec(),
+            Value::U32(x) => x.to_ne_bytes().to_vec(),
+            Value::U64(x) => x.to_ne_bytes().to_vec(),
+            Value::F32(x) => x.to_ne_bytes().to_vec(),
+            Value::F64(x) => x.to_ne_bytes().to_vec(),
+            Value::Bytes(x) => x.clone(),
+        }
+    }
+    fn as_int(&self) -> Option<i128> {
+        Some(match self {
+            Value::I8(x) => *x as i128,
+            Value::I16(x) => *x as i128,
+            Value::I32(x) => *x as i128,
+            Value::I64(x) => *x as i128,
+            Value::U8(x) => *x as i128,
+            Value::U16(x) => *x as i128,
+            Value::U32(x) => *x as i128,
+            Value::U64(x) => *x as i128,
+            _ => return None,
+        })
+    }
+}
+
+macro_rules! value_from {
+    ($($variant:ident($ty:ty)),*) => {
+        $(impl From<$ty> for Value {
+            fn from(value: $ty) -> Self {
+                Value::$variant(value)
+            }
+        })*
+    };
+}
+value_from!(
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    Bytes(Vec<u8>)
+);
+
+impl ScanFilter {
+    fn needs_previous(&self) -> bool {
+        !matches!(
+            self,
+            ScanFilter::Unknown | ScanFilter::Exact(_) | ScanFilter::Between(..)
+        )
+    }
+    fn values(&self) -> Vec<&Value> {
+        match self {
+            ScanFilter::Exact(x) | ScanFilter::IncreasedBy(x) | ScanFilter::DecreasedBy(x) => {
+                vec![x]
+            }
+            ScanFilter::Between(a, b) => vec![a, b],
+            _ => vec![],
+        }
+    }
+}
+
+/// a filter checked against the session's value kind, ready for matching
+struct Matcher<'f> {
+    filter: &'f ScanFilter,
+    kind: ValueKind,
+    tolerance: f64,
+    /// the encoded value for filters which can be checked with a byte comparison
+    exact: Option<Vec<u8>>,
+    operands: Vec<Number>,
+}
+
+impl<'f> Matcher<'f> {
+    fn new(
+        filter: &'f ScanFilter,
+        kind: ValueKind,
+        tolerance: f64,
+    ) -> Result<Self, ValueScanError> {
+        for value in filter.values() {
+            if value.kind() != kind {
+                return Err(ValueScanError::KindMismatch(value.clone(), kind));
+            }
+        }
+        let is_float = matches!(kind, ValueKind::F32 | ValueKind::F64);
+        if let ValueKind::Bytes(_) = kind {
+            if !matches!(
+                filter,
+                ScanFilter::Unknown
+                    | ScanFilter::Exact(_)
+                    | ScanFilter::Changed
+                    | ScanFilter::Unchanged
+            ) {
+                return Err(ValueScanError::UnsupportedFilter(filter.clone(), kind));
+            }
+        }
+        let exact = match filter {
+            ScanFilter::Exact(value) if !is_float => Some(value.to_bytes()),
+            _ => None,
+        };
+        let operands = filter
+            .values()
+            .into_iter()
+            .filter_map(|value| kind.number(&value.to_bytes()))
+            .collect();
+        Ok(Self {
+            filter,
+            kind,
+            tolerance,
+            exact,
+            operands,
+        })
+    }
+    fn eq(&self, a: Number, b: Number) -> bool {
+        match (a, b) {
+            (Number::Float(a), Number::Float(b)) => (a - b).abs() <= self.tolerance,
+            _ => a == b,
+        }
+    }
+    fn diff(a: Number, b: Number) -> Number {
+        match (a, b) {
+            (Number::Int(a), Number::Int(b)) => Number::Int(a - b),
+            (Number::Float(a), Number::Float(b)) => Number::Float(a - b),
+            _ => unreachable!("numbers of a scan are always of the same kind"),
+        }
+    }
+    fn matches(&self, current: &[u8], previous: Option<&[u8]>) -> bool {
+        if let Some(exact) = &self.exact {
+            return current == exact.as_slice();
+        }
+        match (self.filter, previous) {
+            (ScanFilter::Unknown, _) => return true,
+            (ScanFilter::Changed, Some(previous)) => {
+                if let ValueKind::Bytes(_) = self.kind {
+                    return current != previous;
+                }
+            }
+            (ScanFilter::Unchanged, Some(previous)) => {
+                if let ValueKind::Bytes(_) = self.kind {
+                    return current == previous;
+                }
+            }
+            _ => {}
+        }
+        let Some(current) = self.kind.number(current) else {
+            return false;
+        };
+        let previous = previous.and_then(|previous| self.kind.number(previous));
+        // NaNs never match anything
+        if matches!(current, Number::Float(x) if x.is_nan()) {
+            return false;
+        }
+        match (self.filter, previous) {
+            (ScanFilter::Exact(_), _) => self.eq(current, self.operands[0]),
+            (ScanFilter::Between(..), _) => {
+                self.operands[0] <= current && current <= self.operands[1]
+            }
+            (ScanFilter::Changed, Some(previous)) => !self.eq(current, previous),
+            (ScanFilter::Unchanged, Some(previous)) => self.eq(current, previous),
+            (ScanFilter::Increased, Some(previous)) => {
+                current > previous && !self.eq(current, previous)
+            }
+            (ScanFilter::Decreased, Some(previous)) => {
+                current < previous && !self.eq(current, previous)
+            }
+            (ScanFilter::IncreasedBy(_), Some(previous)) => {
+                self.eq(Self::diff(current, previous), self.operands[0])
+            }
+            (ScanFilter::DecreasedBy(_), Some(previous)) => {
+                self.eq(Self::diff(previous, current), self.operands[0])
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<'a, T: Mem> ValueScanSession<'a, T> {
+    /// create a session searching for values of <kind> in <process>.
+    /// values are expected to be aligned to their size (or 1 for byte arrays)
+    pub fn new(process: &'a T, kind: ValueKind) -> Self {
+        let alignment = match kind {
+            ValueKind::Bytes(_) => 1,
+            kind => kind.size(),
+        };
+        Self {
+            process,
+            kind,
+            alignment,
+            tolerance: 0.0,
+            range: (0, usize::MAX),
+            chunks: None,
+        }
+    }
+    /// only consider values at addresses aligned to <alignment>
+    pub fn with_alignment(mut self, alignment: usize) -> Self {
+        self.alignment = alignment.max(1);
+        self
+    }
+    /// the tolerance used when comparing floats
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+    /// only scan memory between <start> and <end>
+    pub fn with_range(mut self, start: usize, end: usize) -> Self {
+        self.range = (start, end);
+        self
+    }
+    /// get the kind of value being searched for
+    pub const fn get_kind(&self) -> ValueKind {
+        self.kind
+    }
+    /// scan every readable and writable region, replacing any previous results.
+    /// returns the amount of candidates found.
+    /// # Safety
+    /// reads every writable region of the process.
+    pub unsafe fn first_scan(&mut self, filter: ScanFilter) -> Result<usize, ValueScanError> {
+        if filter.needs_previous() {
+            return Err(ValueScanError::NeedsPreviousScan(filter));
+        }
+        let matcher = Matcher::new(&filter, self.kind, self.tolerance)?;
+        let size = self.kind.size();
+        let mut chunks = vec![];
+        let regions = self.process.regions()?.filter(|region| {
+            region.is_readable()
+                && region.is_writable()
+                && region.get_start() < self.range.1
+                && region.get_end() > self.range.0
+        });
+        // chunks start aligned and overlap by a value, so values crossing into the next chunk
+        // are found by the chunk they start in
+        let step = (CHUNK_SIZE / self.alignment).max(1) * self.alignment;
+        for region in regions {
+            let start = region
+                .get_start()
+                .max(self.range.0)
+                .next_multiple_of(self.alignment);
+            let end = region.get_end().min(self.range.1);
+            for chunk_start in (start..end).step_by(step) {
+                let chunk_end = end.min(chunk_start.saturating_add(step + size - 1));
+                let Ok(data) = self
+                    .process
+                    .read_sized(chunk_start, chunk_end - chunk_start)
+                else {
+                    continue;
+                };
+                if let ScanFilter::Unknown = filter {
+                    chunks.push(Chunk {
+                        start: chunk_start,
+                        candidates: Candidates::Dense { data, hits: None },
+                    });
+                    continue;
+                }
+                let offsets = self
+                    .offsets(chunk_start, data.len())
+                    .filter(|offset| matcher.matches(&data[*offset..*offset + size], None))
+                    .map(|offset| offset as u32)
+                    .collect();
+                chunks.extend(Chunk::pack(chunk_start, data, offsets, size));
+            }
+        }
+        self.chunks = Some(chunks);
+        Ok(self.len())
+    }
+    /// narrow down the candidates of the previous scan with <filter>, candidates which can no
+    /// longer be read are dropped. returns the amount of candidates left.
+    /// # Safety
+    /// reads the memory of every candidate.
+    pub unsafe fn next_scan(&mut self, filter: ScanFilter) -> Result<usize, ValueScanError> {
+        let matcher = Matcher::new(&filter, self.kind, self.tolerance)?;
+        let size = self.kind.size();
+        let Some(chunks) = self.chunks.take() else {
+            return Err(ValueScanError::NoFirstScan);
+        };
+        let mut narrowed = vec![];
+        for chunk in chunks {
+            let (Some((first, _)), Some((last, _))) = (
+                self.candidates(&chunk).next(),
+                self.candidates(&chunk).last(),
+            ) else {
+                continue;
+            };
+            // only read the part of the chunk which still holds candidates
+            let Ok(data) = self
+                .process
+                .read_sized(chunk.start + first, last - first + size)
+            else {
+                continue;
+            };
+            let offsets = self
+                .candidates(&chunk)
+                .filter(|(offset, old)| {
+                    let at = offset - first;
+                    matcher.matches(&data[at..at + size], Some(old))
+                })
+                .map(|(offset, _)| (offset - first) as u32)
+                .collect();
+            narrowed.extend(Chunk::pack(chunk.start + first, data, offsets, size));
+        }
+        self.chunks = Some(narrowed);
+        Ok(self.len())
+    }
+    /// the amount of candidates
+    pub fn len(&self) -> usize {
+        self.chunks
+            .iter()
+            .flatten()
+            .map(|chunk| self.candidates(chunk).count())
+            .sum()
+    }
+    /// if there are no candidates
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// get every candidate address with the value it had during the last scan
+    pub fn results(&self) -> impl Iterator<Item = (usize, Value)> + '_ {
+        self.chunks.iter().flatten().flat_map(move |chunk| {
+            self.candidates(chunk)
+                .map(move |(offset, value)| (chunk.start + offset, self.kind.decode(value)))
+        })
+    }
+    /// the offsets of the candidates in <chunk> with their value during the last scan
+    fn candidates<'c>(
+        &'c self,
+        chunk: &'c Chunk,
+    ) -> Box<dyn Iterator<Item = (usize, &'c [u8])> + 'c> {
+        let size = self.kind.size();
+        match &chunk.candidates {
+            Candidates::Dense { data, hits: None } => Box::new(
+                self.offsets(chunk.start, data.len())
+                    .map(move |offset| (offset, &data[offset..offset + size])),
+            ),
+            Candidates::Dense {
+                data,
+                hits: Some(hits),
+            } => Box::new(
+                hits.iter()
+                    .enumerate()
+                    .flat_map(|(i, word)| {
+                        let mut word = *word;
+                        std::iter::from_fn(move || {
+                            let bit = (word != 0).then(|| word.trailing_zeros() as usize)?;
+                            word &= word - 1;
+                            Some(i * 64 + bit)
+                        })
+                    })
+                    .map(move |offset| (offset, &data[offset..offset + size])),
+            ),
+            Candidates::Sparse { offsets, previous } => Box::new(
+                offsets
+                    .iter()
+                    .map(|x| *x as usize)
+                    .zip(previous.chunks(size)),
+            ),
+        }
+    }
+    /// the aligned offsets into a chunk at which a whole value fits
+    fn offsets(&self, start: usize, len: usize) -> impl Iterator<Item = usize> {
+        let first = start.next_multiple_of(self.alignment) - start;
+        let end = (len + 1).saturating_sub(self.kind.size());
+        (first..end).step_by(self.alignment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScanFilter, Value, ValueKind, ValueScanError, ValueScanSession};
+    use crate::structures::process::Process;
+
+    fn contains<T: crate::traits::Mem>(session: &ValueScanSession<T>, addr: usize) -> bool {
+        session.results().any(|(at, _)| at == addr)
+    }
+
+    #[test]
+    fn test_int_scan() {
+        let proc = Process::find_pid(std::process::id()).unwrap();
+        let mut values = Box::new([0i32; 0x100]);
+        values[0x10] = 0x7123_4567;
+        let addr = &values[0x10] as *const i32 as usize;
+        let (start, end) = (values.as_ptr() as usize, values.as_ptr() as usize + 0x400);
+        let mut session = ValueScanSession::new(&proc, ValueKind::I32).with_range(start, end);
+        unsafe {
+            assert!(matches!(
+                session.next_scan(ScanFilter::Changed),
+                Err(ValueScanError::NoFirstScan)
+            ));
+            assert!(matches!(
+                session.first_scan(ScanFilter::Increased),
+                Err(ValueScanError::NeedsPreviousScan(_))
+            ));
+            assert!(matches!(
+                session.first_scan(ScanFilter::Exact(Value::I64(1))),
+                Err(ValueScanError::KindMismatch(..))
+            ));
+
+            session.first_scan(ScanFilter::Unknown).unwrap();
+            assert_eq!(session.len(), 0x100);
+
+            values[0x10] += 5;
+            values[0x20] = -1;
+            assert_eq!(session.next_scan(ScanFilter::Changed).unwrap(), 2);
+            // nothing changed since the last scan
+            assert_eq!(
+                session
+                    .next_scan(ScanFilter::IncreasedBy(Value::I32(0)))
+                    .unwrap(),
+                2
+            );
+            assert_eq!(session.next_scan(ScanFilter::Changed).unwrap(), 0);
+
+            session
+                .first_scan(ScanFilter::Between(Value::I32(1), Value::I32(i32::MAX)))
+                .unwrap();
+            assert_eq!(session.len(), 1);
+            assert!(contains(&session, addr));
+
+            values[0x10] -= 3;
+            session.next_scan(ScanFilter::Decreased).unwrap();
+            assert!(contains(&session, addr));
+            values[0x10] += 7;
+            session
+                .next_scan(ScanFilter::IncreasedBy(Value::I32(7)))
+                .unwrap();
+            assert!(contains(&session, addr));
+            session.next_scan(ScanFilter::Unchanged).unwrap();
+            assert_eq!(session.len(), 1);
+            assert_eq!(
+                session.results().next().unwrap().1,
+                Value::I32(0x7123_4567 + 9)
+            );
+        }
+    }
+
+    #[test]
+    fn test_float_and_bytes_scan() {
+        let proc = Process::find_pid(std::process::id()).unwrap();
+        let mut values = Box::new([0f32; 0x40]);
+        values[3] = 100.25;
+        let (start, end) = (values.as_ptr() as usize, values.as_ptr() as usize + 0x100);
+        let addr = &values[3] as *const f32 as usize;
+        unsafe {
+            let mut session = ValueScanSession::new(&proc, ValueKind::F32)
+                .with_range(start, end)
+                .with_tolerance(0.5);
+            session
+                .first_scan(ScanFilter::Exact(100.0f32.into()))
+                .unwrap();
+            assert!(contains(&session, addr));
+            values[3] = 101.0;
+            session
+                .next_scan(ScanFilter::IncreasedBy(0.75f32.into()))
+                .unwrap();
+            assert!(contains(&session, addr));
+
+            let mut bytes = Box::new([0u8; 0x40]);
+            bytes[5..9].copy_from_slice(b"pogs");
+            let (start, end) = (bytes.as_ptr() as usize, bytes.as_ptr() as usize + 0x40);
+            let mut session =
+                ValueScanSession::new(&proc, ValueKind::Bytes(4)).with_range(start, end);
+            assert_eq!(
+                session
+                    .first_scan(ScanFilter::Exact(b"pogs".to_vec().into()))
+                    .unwrap(),
+                1
+            );
+            assert_eq!(session.results().next().unwrap().0, start + 5);
+            assert!(matches!(
+                session.next_scan(ScanFilter::Increased),
+                Err(ValueScanError::UnsupportedFilter(..))
+            ));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_chunks() {
+        use super::{Candidates, CHUNK_SIZE};
+        use crate::{
+            structures::{mock::MockProcess, protections::Protections},
+            traits::Mem,
+        };
+
+        let base = 0x1000_0000;
+        let mut data = vec![0u8; CHUNK_SIZE + 0x1000];
+        // aligned, but across the chunk boundary of a range starting five bytes in
+        data[CHUNK_SIZE + 4..CHUNK_SIZE + 8].copy_from_slice(&0x1337u32.to_ne_bytes());
+        // unaligned, across the boundary of a range starting at the base
+        data[CHUNK_SIZE - 2..CHUNK_SIZE + 2].copy_from_slice(b"pogs");
+        let proc = MockProcess::new("game").with_region(
+            base,
+            data,
+            Protections::new().with_read(true).with_write(true),
+        );
+        let end = base + CHUNK_SIZE + 0x1000;
+        unsafe {
+            let mut session =
+                ValueScanSession::new(&proc, ValueKind::U32).with_range(base + 5, end);
+            assert_eq!(
+                session
+                    .first_scan(ScanFilter::Exact(0x1337u32.into()))
+                    .unwrap(),
+                1
+            );
+            assert!(contains(&session, base + CHUNK_SIZE + 4));
+
+            let mut session =
+                ValueScanSession::new(&proc, ValueKind::Bytes(4)).with_range(base, end);
+            session
+                .first_scan(ScanFilter::Exact(b"pogs".to_vec().into()))
+                .unwrap();
+            assert_eq!(session.results().next().unwrap().0, base + CHUNK_SIZE - 2);
+
+            // nearly everything is zero, so the candidates are kept as a bitmap
+            let mut session =
+                ValueScanSession::new(&proc, ValueKind::U8).with_range(base, base + 0x10000);
+            let zeroes = session.first_scan(ScanFilter::Exact(0u8.into())).unwrap();
+            assert_eq!(zeroes, 0x10000);
+            assert!(session
+                .chunks
+                .iter()
+                .flatten()
+                .all(|chunk| matches!(chunk.candidates, Candidates::Dense { .. })));
+            proc.write(base + 0x10, &5u8).unwrap();
+            assert_eq!(
+                session.next_scan(ScanFilter::Unchanged).unwrap(),
+                zeroes - 1
+            );
+            assert!(!contains(&session, base + 0x10));
+            assert_eq!(session.next_scan(ScanFilter::Changed).unwrap(), 0);
+        }
+    }
+}
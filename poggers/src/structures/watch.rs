@@ -0,0 +1,287 @@
+use std::{
+    collections::HashMap,
+    sync::mpsc::{Receiver, Sender},
+    time::{Duration, Instant, SystemTime},
+};
+
+use super::poller::{Poll, Poller};
+use crate::traits::{Mem, Pod, ReadRequest};
+
+/// polls addresses of a process from a background thread and reports when they change.
+/// every watch is read with a single [Mem::read_scatter] per poll.
+/// the thread stops when the watcher is dropped.
+/// ```rs
+/// let watcher = Watcher::new(process.clone(), Duration::from_millis(10))?;
+/// unsafe { watcher.watch_value::<u32>(ammo_addr) };
+/// for event in watcher.get_events() {
+///     println!("{:X}: {:?} -> {:?}", event.get_addr(), event.old_as::<u32>(), event.new_as::<u32>());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Watcher {
+    poller: Poller<Command>,
+    events: Receiver<WatchEvent>,
+}
+
+/// identifies a watch of a [Watcher]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WatchId(u64);
+
+/// a change of a watched range of memory
+#[derive(Debug, Clone)]
+pub struct WatchEvent {
+    pub(crate) id: WatchId,
+    pub(crate) addr: usize,
+    pub(crate) old: Vec<u8>,
+    pub(crate) new: Vec<u8>,
+    pub(crate) timestamp: SystemTime,
+}
+
+/// a callback of [Watcher::watch_with], called on the watcher thread
+type Callback = Box<dyn FnMut(&WatchEvent) + Send>;
+
+enum Command {
+    Watch(WatchId, Watch),
+    Unwatch(WatchId),
+}
+
+/// the state of the watcher thread
+struct Watching<T> {
+    process: T,
+    interval: Duration,
+    next: Instant,
+    watches: HashMap<WatchId, Watch>,
+    events: Sender<WatchEvent>,
+}
+
+struct Watch {
+    addr: usize,
+    size: usize,
+    previous: Option<Vec<u8>>,
+    callback: Option<Callback>,
+}
+
+impl WatchEvent {
+    /// get the watch which changed
+    pub const fn get_id(&self) -> WatchId {
+        self.id
+    }
+    /// get the address of the watched memory
+    pub const fn get_addr(&self) -> usize {
+        self.addr
+    }
+    /// get the contents before the change
+    pub fn get_old(&self) -> &[u8] {
+        &self.old
+    }
+    /// get the contents after the change
+    pub fn get_new(&self) -> &[u8] {
+        &self.new
+    }
+    /// get when the change was noticed
+    pub const fn get_timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+    /// get the contents before the change as <V>, if the watch is the size of <V>
+    pub fn old_as<V: Pod>(&self) -> Option<V> {
+        V::from_bytes(&self.old)
+    }
+    /// get the contents after the change as <V>, if the watch is the size of <V>
+    pub fn new_as<V: Pod>(&self) -> Option<V> {
+        V::from_bytes(&self.new)
+    }
+}
+
+impl Watcher {
+    /// start a watcher polling <process> every <interval>, fails if the thread can't be spawned
+    pub fn new<T>(process: T, interval: Duration) -> std::io::Result<Self>
+    where
+        T: Mem + Send + Sync + 'static,
+    {
+        let (sender, events) = std::sync::mpsc::channel();
+        let state = Watching {
+            process,
+            interval,
+            next: Instant::now(),
+            watches: HashMap::new(),
+            events: sender,
+        };
+        Ok(Self {
+            poller: Poller::spawn("poggers-watcher", state)?,
+            events,
+        })
+    }
+    /// watch <size> bytes at <addr>, changes are sent to [Watcher::get_events]
+    /// # Safety
+    /// the reads happen with [Mem::read_scatter] from the watcher thread, <addr> must be safe to
+    /// read for as long as it is watched.
+    pub unsafe fn watch(&self, addr: impl Into<usize>, size: usize) -> WatchId {
+        self.add(addr.into(), size, None)
+    }
+    /// watch a <V> at <addr>, changes are sent to [Watcher::get_events]
+    /// # Safety
+    /// see [Watcher::watch]
+    pub unsafe fn watch_value<V: Pod>(&self, addr: impl Into<usize>) -> WatchId {
+        self.add(addr.into(), std::mem::size_of::<V>(), None)
+    }
+    /// watch <size> bytes at <addr>, calling <callback> on the watcher thread for every change
+    /// instead of sending it to [Watcher::get_events]
+    /// # Safety
+    /// see [Watcher::watch]
+    pub unsafe fn watch_with(
+        &self,
+        addr: impl Into<usize>,
+        size: usize,
+        callback: impl FnMut(&WatchEvent) + Send + 'static,
+    ) -> WatchId {
+        self.add(addr.into(), size, Some(Box::new(callback)))
+    }
+    /// stop watching
+    pub fn unwatch(&self, id: WatchId) {
+        self.poller.send(Command::Unwatch(id));
+    }
+    /// get the channel changes are reported on
+    pub fn get_events(&self) -> &Receiver<WatchEvent> {
+        &self.events
+    }
+    fn add(&self, addr: usize, size: usize, callback: Option<Callback>) -> WatchId {
+        let id = WatchId(self.poller.next_id());
+        let watch = Watch {
+            addr,
+            size,
+            previous: None,
+            callback,
+        };
+        self.poller.send(Command::Watch(id, watch));
+        id
+    }
+}
+
+impl<T: Mem + Send + 'static> Poll for Watching<T> {
+    type Command = Command;
+
+    fn handle(&mut self, command: Command) {
+        match command {
+            Command::Watch(id, watch) => {
+                self.watches.insert(id, watch);
+            }
+            Command::Unwatch(id) => {
+                self.watches.remove(&id);
+            }
+        }
+    }
+    fn poll(&mut self) -> Option<Instant> {
+        if self.watches.is_empty() {
+            return None;
+        }
+        let now = Instant::now();
+        if self.next > now {
+            return Some(self.next);
+        }
+        self.next = now + self.interval;
+
+        let mut buffers = self
+            .watches
+            .values()
+            .map(|watch| vec![0u8; watch.size])
+            .collect::<Vec<_>>();
+        let results = {
+            let mut requests = self
+                .watches
+                .values()
+                .zip(buffers.iter_mut())
+                .map(|(watch, buf)| ReadRequest::new(watch.addr, buf))
+                .collect::<Vec<_>>();
+            // the addresses were promised to be readable by Watcher::watch
+            unsafe { self.process.read_scatter(&mut requests) }
+        };
+        let timestamp = SystemTime::now();
+        for (((id, watch), current), result) in self.watches.iter_mut().zip(buffers).zip(results) {
+            // unreadable memory keeps the last known contents
+            if result.is_err() {
+                continue;
+            }
+            let Some(previous) = watch.previous.replace(current.clone()) else {
+                continue;
+            };
+            if previous == current {
+                continue;
+            }
+            let event = WatchEvent {
+                id: *id,
+                addr: watch.addr,
+                old: previous,
+                new: current,
+                timestamp,
+            };
+            match &mut watch.callback {
+                Some(callback) => callback(&event),
+                None => {
+                    self.events.send(event).ok();
+                }
+            }
+        }
+        Some(self.next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            mpsc::channel,
+        },
+        time::Duration,
+    };
+
+    use super::Watcher;
+    use crate::structures::process::Process;
+
+    #[test]
+    fn test_watcher() {
+        let value = Box::new(AtomicU32::new(1));
+        let other = Box::new(AtomicU32::new(1));
+        let addr = &*value as *const AtomicU32 as usize;
+        let other_addr = &*other as *const AtomicU32 as usize;
+        let watcher = Watcher::new(
+            Process::find_pid(std::process::id()).unwrap(),
+            Duration::from_millis(1),
+        )
+        .unwrap();
+        let wait = || std::thread::sleep(Duration::from_millis(50));
+
+        let (sender, callback_events) = channel();
+        let id = unsafe {
+            watcher.watch_with(other_addr, 4, move |event| {
+                sender.send(event.new_as::<u32>()).ok();
+            });
+            watcher.watch_value::<u32>(addr)
+        };
+        wait();
+        value.store(2, Ordering::SeqCst);
+        other.store(5, Ordering::SeqCst);
+
+        let event = watcher
+            .get_events()
+            .recv_timeout(Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(event.get_id(), id);
+        assert_eq!(event.get_addr(), addr);
+        assert_eq!(event.old_as::<u32>(), Some(1));
+        assert_eq!(event.new_as::<u32>(), Some(2));
+        assert_eq!(event.new_as::<u64>(), None);
+        assert_eq!(
+            callback_events
+                .recv_timeout(Duration::from_secs(1))
+                .unwrap(),
+            Some(5)
+        );
+
+        watcher.unwatch(id);
+        wait();
+        value.store(3, Ordering::SeqCst);
+        wait();
+        assert!(watcher.get_events().try_recv().is_err());
+    }
+}
@@ -1,6 +1,8 @@
 use tracing_subscriber::{layer::SubscriberExt, Registry};
 use tracing_tree::HierarchicalLayer;
 
+use crate::structures::protections::Protections;
+
 pub(crate) fn init_tracing() {
     let layer = HierarchicalLayer::default()
         .with_writer(std::io::stdout)
@@ -12,3 +14,20 @@ pub(crate) fn init_tracing() {
     let subscriber = Registry::default().with(layer);
     tracing::subscriber::set_global_default(subscriber).unwrap();
 }
+
+#[cfg(unix)]
+pub(crate) fn read_only() -> Protections {
+    Protections::new().with_read(true)
+}
+#[cfg(unix)]
+pub(crate) fn read_write() -> Protections {
+    Protections::new().with_read(true).with_write(true)
+}
+#[cfg(windows)]
+pub(crate) fn read_only() -> Protections {
+    Protections::ReadOnly
+}
+#[cfg(windows)]
+pub(crate) fn read_write() -> Protections {
+    Protections::ReadWrite
+}
@@ -1,8 +1,13 @@
+use std::ffi::OsString;
+
 use thiserror::Error;
 
 use crate::{
-    sigscan::SigScan,
-    structures::{addr::Address, process::ProcessError, virtalloc::VirtAlloc},
+    sigscan::{pattern::PatternError, SigScan},
+    structures::{
+        addr::Address, modules::ModuleError, process::ProcessError, region::MemoryRegion,
+        virtalloc::VirtAlloc,
+    },
 };
 
 use super::structures::protections::Protections;
@@ -14,29 +19,25 @@ pub trait Mem {
     /// ```rs
     /// let data: u32 = process.read::<u32>(0x12345678)?;
     /// ```
+    /// prefer [SafeMem::read_value] where the process implements it, which is safe.
     /// # Safety
     /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
-    unsafe fn read<T>(&self, addr: usize) -> Result<T, MemError> {
-        let mut data: T = std::mem::zeroed();
+    unsafe fn read<T: Pod>(&self, addr: usize) -> Result<T, MemError> {
+        let mut data = std::mem::MaybeUninit::<T>::uninit();
         // if Self::READ_REQUIRE_PROTECTION {
         //     let old = self.alter_protection(addr, std::mem::size_of::<T>(), Protections::ExecuteReadWrite)?;
         //     self.raw_read(addr, &mut data as *mut T as *mut u8, std::mem::size_of::<T>())?;
         //     self.alter_protection(addr, std::mem::size_of::<T>(), old)?;
         // }
         // else {
-        self.raw_read(
-            addr,
-            &mut data as *mut T as *mut u8,
-            std::mem::size_of::<T>(),
-        )?;
+        self.raw_read(addr, data.as_mut_ptr() as *mut u8, std::mem::size_of::<T>())?;
         // }
         // self.raw_read(addr, &mut data as *mut T as *mut u8, std::mem::size_of::<T>())?;
-        Ok(data)
+        Ok(data.assume_init())
     }
     /// Read raw bytes from memory at address <addr> with size <size>
     /// # Safety
     /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
-
     unsafe fn read_sized(&self, addr: usize, size: usize) -> Result<Vec<u8>, MemError> {
         let mut data: Vec<u8> = vec![0; size];
         // if Self::READ_REQUIRE_PROTECTION {
@@ -46,29 +47,57 @@ pub trait Mem {
         // }
         Ok(data)
     }
+    /// Read many blocks of memory at once, filling the buffer of each request in place.
+    /// Returns the result of every request, in the same order as <requests>.
+    /// By default this reads each request one by one, platforms which can batch reads override it.
+    /// # Safety
+    /// unsafe because it does direct calls to the OS. the addresses supplied could be invalid.
+    unsafe fn read_scatter(&self, requests: &mut [ReadRequest<'_>]) -> Vec<Result<(), MemError>> {
+        requests
+            .iter_mut()
+            .map(|request| self.raw_read(request.addr, request.buf.as_mut_ptr(), request.buf.len()))
+            .collect()
+    }
+    /// Read <T> from every address in <addrs> using [Mem::read_scatter].
+    /// Returns the result of every read, in the same order as <addrs>.
+    /// ```rs
+    /// let healths = process.read_many::<f32>(&[0x1000, 0x2000, 0x3000]);
+    /// ```
+    /// # Safety
+    /// unsafe because it does direct calls to the OS. the addresses supplied could be invalid.
+    unsafe fn read_many<T: Pod>(&self, addrs: &[usize]) -> Vec<Result<T, MemError>> {
+        let size = std::mem::size_of::<T>();
+        let mut data = vec![0u8; size * addrs.len()];
+        let results = if size == 0 {
+            addrs.iter().map(|_| Ok(())).collect()
+        } else {
+            let mut requests: Vec<ReadRequest> = addrs
+                .iter()
+                .zip(data.chunks_mut(size))
+                .map(|(addr, buf)| ReadRequest::new(*addr, buf))
+                .collect();
+            self.read_scatter(&mut requests)
+        };
+        results
+            .into_iter()
+            .enumerate()
+            .map(|(i, res)| {
+                res.map(|_| {
+                    T::from_bytes(&data[i * size..(i + 1) * size])
+                        .expect("every chunk is the size of T")
+                })
+            })
+            .collect()
+    }
     /// Write <T> to memory at address <addr>
     /// # Safety
     /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
-
-    unsafe fn write<T>(&self, addr: usize, data: &T) -> Result<(), MemError> {
-        // if Self::WRITE_REQUIRE_PROTECTION {
-        //     let old = self.alter_protection(addr, std::mem::size_of::<T>(),Protections::ReadWrite)?;
-        //     self.raw_write(addr, data as *const T as *const u8, std::mem::size_of::<T>())?;
-        //     self.alter_protection(addr, std::mem::size_of::<T>(), old)?;
-        // }
-        // else {
-        self.raw_write(
-            addr,
-            data as *const T as *const u8,
-            std::mem::size_of::<T>(),
-        )?;
-        // }
-        Ok(())
+    unsafe fn write<T: Pod>(&self, addr: usize, data: &T) -> Result<(), MemError> {
+        self.write_raw(addr, data.as_bytes())
     }
     /// Write raw bytes to memory at address <addr>
     /// # Safety
     /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
-
     unsafe fn write_raw(&self, addr: usize, data: &[u8]) -> Result<(), MemError> {
         // if Self::WRITE_REQUIRE_PROTECTION {
         //     let old = self.alter_protection(addr, data.len(),Protections::ReadWrite)?;
@@ -80,17 +109,182 @@ pub trait Mem {
         // }
         Ok(())
     }
+    /// the size of pointers in the process, which may differ from this build when inspecting a
+    /// 32-bit process from a 64-bit build. defaults to [PointerWidth::NATIVE]
+    fn pointer_width(&self) -> PointerWidth {
+        PointerWidth::NATIVE
+    }
+    /// Read a pointer of the process at address <addr>, sized by [Mem::pointer_width]
+    /// ```rs
+    /// let entity_list = process.read_ptr(client_base + 0x4DA215C)?;
+    /// ```
+    /// # Safety
+    /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
+    unsafe fn read_ptr(&self, addr: usize) -> Result<usize, MemError> {
+        match self.pointer_width() {
+            PointerWidth::Bits32 => self.read::<u32>(addr).map(|x| x as usize),
+            PointerWidth::Bits64 => self.read::<u64>(addr).map(|x| x as usize),
+        }
+    }
+    /// Write a pointer of the process to address <addr>, sized by [Mem::pointer_width].
+    /// for 32-bit processes the upper bits of <value> are dropped
+    /// # Safety
+    /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
+    unsafe fn write_ptr(&self, addr: usize, value: usize) -> Result<(), MemError> {
+        match self.pointer_width() {
+            PointerWidth::Bits32 => self.write(addr, &(value as u32)),
+            PointerWidth::Bits64 => self.write(addr, &(value as u64)),
+        }
+    }
+    /// Read a NUL terminated string of at most <max_len> bytes at address <addr>, without the
+    /// terminator. memory is read a page at a time, so a string ending right before an unmapped
+    /// page can still be read. if no terminator is found within <max_len> bytes the first
+    /// <max_len> bytes are returned.
+    /// # Safety
+    /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
+    unsafe fn read_c_bytes(&self, addr: usize, max_len: usize) -> Result<Vec<u8>, MemError> {
+        let mut data = vec![];
+        while data.len() < max_len {
+            let at = addr + data.len();
+            let chunk = (STRING_CHUNK_SIZE - at % STRING_CHUNK_SIZE).min(max_len - data.len());
+            let chunk = self.read_sized(at, chunk)?;
+            if let Some(end) = chunk.iter().position(|x| *x == 0) {
+                data.extend_from_slice(&chunk[..end]);
+                return Ok(data);
+            }
+            data.extend_from_slice(&chunk);
+        }
+        Ok(data)
+    }
+    /// Read a NUL terminated UTF-8 string of at most <max_len> bytes at address <addr>, see
+    /// [Mem::read_c_bytes]. fails with [MemError::InvalidString] if it isn't valid UTF-8.
+    /// # Safety
+    /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
+    unsafe fn read_c_string(&self, addr: usize, max_len: usize) -> Result<String, MemError> {
+        String::from_utf8(self.read_c_bytes(addr, max_len)?)
+            .map_err(|_| MemError::InvalidString(addr))
+    }
+    /// Read a NUL terminated string of at most <max_len> bytes at address <addr>, replacing
+    /// invalid UTF-8 with `U+FFFD`, see [Mem::read_c_bytes].
+    /// # Safety
+    /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
+    unsafe fn read_c_string_lossy(&self, addr: usize, max_len: usize) -> Result<String, MemError> {
+        Ok(String::from_utf8_lossy(&self.read_c_bytes(addr, max_len)?).into_owned())
+    }
+    /// Read a NUL terminated wide string of at most <max_len> u16s at address <addr>, without the
+    /// terminator. read a page at a time like [Mem::read_c_bytes].
+    /// # Safety
+    /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
+    unsafe fn read_wide_chars(&self, addr: usize, max_len: usize) -> Result<Vec<u16>, MemError> {
+        let mut data: Vec<u16> = vec![];
+        while data.len() < max_len {
+            let at = addr + data.len() * 2;
+            let chunk = ((STRING_CHUNK_SIZE - at % STRING_CHUNK_SIZE) / 2)
+                .max(1)
+                .min(max_len - data.len());
+            let chunk = self.read_sized(at, chunk * 2)?;
+            let chars = chunk
+                .chunks_exact(2)
+                .map(|x| u16::from_ne_bytes([x[0], x[1]]));
+            for char in chars {
+                if char == 0 {
+                    return Ok(data);
+                }
+                data.push(char);
+            }
+        }
+        Ok(data)
+    }
+    /// Read a NUL terminated UTF-16 string of at most <max_len> u16s at address <addr>, see
+    /// [Mem::read_wide_chars]. fails with [MemError::InvalidString] if it isn't valid UTF-16.
+    /// # Safety
+    /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
+    unsafe fn read_wide_string(&self, addr: usize, max_len: usize) -> Result<String, MemError> {
+        String::from_utf16(&self.read_wide_chars(addr, max_len)?)
+            .map_err(|_| MemError::InvalidString(addr))
+    }
+    /// Read a NUL terminated UTF-16 string of at most <max_len> u16s at address <addr>, replacing
+    /// unpaired surrogates with `U+FFFD`, see [Mem::read_wide_chars].
+    /// # Safety
+    /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
+    unsafe fn read_wide_string_lossy(
+        &self,
+        addr: usize,
+        max_len: usize,
+    ) -> Result<String, MemError> {
+        Ok(String::from_utf16_lossy(
+            &self.read_wide_chars(addr, max_len)?,
+        ))
+    }
+    /// Read a NUL terminated native string of at most <max_len> characters at address <addr>.
+    /// on windows this is a wide string, elsewhere it is a byte string.
+    /// # Safety
+    /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
+    unsafe fn read_os_string(&self, addr: usize, max_len: usize) -> Result<OsString, MemError> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStringExt;
+            Ok(OsString::from_vec(self.read_c_bytes(addr, max_len)?))
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::ffi::OsStringExt;
+            Ok(OsString::from_wide(&self.read_wide_chars(addr, max_len)?))
+        }
+    }
+    /// Read a string prefixed with its length in bytes as a <L> (e.g. u8, u16, u32) at address
+    /// <addr>. fails with [MemError::InvalidString] if the length is above <max_len> or the
+    /// string isn't valid UTF-8.
+    /// # Safety
+    /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
+    unsafe fn read_prefixed_string<L>(
+        &self,
+        addr: usize,
+        max_len: usize,
+    ) -> Result<String, MemError>
+    where
+        L: Pod + TryInto<usize>,
+    {
+        let len = self
+            .read::<L>(addr)?
+            .try_into()
+            .map_err(|_| MemError::InvalidString(addr))?;
+        if len > max_len {
+            return Err(MemError::InvalidString(addr));
+        }
+        String::from_utf8(self.read_sized(addr + std::mem::size_of::<L>(), len)?)
+            .map_err(|_| MemError::InvalidString(addr))
+    }
+    /// Write <data> followed by a NUL terminator to memory at address <addr>
+    /// # Safety
+    /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
+    unsafe fn write_c_string(&self, addr: usize, data: &str) -> Result<(), MemError> {
+        let mut bytes = Vec::with_capacity(data.len() + 1);
+        bytes.extend_from_slice(data.as_bytes());
+        bytes.push(0);
+        self.write_raw(addr, &bytes)
+    }
+    /// Write <data> as UTF-16 followed by a NUL terminator to memory at address <addr>
+    /// # Safety
+    /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
+    unsafe fn write_wide_string(&self, addr: usize, data: &str) -> Result<(), MemError> {
+        let bytes = data
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .flat_map(u16::to_ne_bytes)
+            .collect::<Vec<_>>();
+        self.write_raw(addr, &bytes)
+    }
     /// Fetch a page of memory at address <addr>
     /// # Safety
     /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
-
     unsafe fn fetch_page(&self, addr: usize) -> Result<[u8; 0x1000], MemError> {
         let mut data: [u8; 0x1000] = [0; 0x1000];
         self.raw_read(addr, data.as_mut_ptr(), 0x1000)?;
         Ok(data)
     }
     /// get a wrapper around an address
-    fn address(&self, size: usize) -> Address<Self>
+    fn address(&self, size: usize) -> Address<'_, Self>
     where
         Self: Sized + SigScan + Clone,
     {
@@ -106,7 +300,7 @@ pub trait Mem {
         addr: Option<usize>,
         size: usize,
         prot: Protections,
-    ) -> Result<VirtAlloc<Self>, MemError>
+    ) -> Result<VirtAlloc<'_, Self>, MemError>
     where
         Self: Sized,
     {
@@ -117,6 +311,16 @@ pub trait Mem {
             proc: self,
         })
     }
+    /// Query the memory region which contains address <addr>
+    fn query(&self, addr: usize) -> Result<MemoryRegion, MemError> {
+        self.regions()?
+            .find(|region| region.contains(addr))
+            .ok_or(MemError::NoRegion(addr))
+    }
+    /// Get an iterator over every memory region mapped in the process, needs implementation per platform
+    fn regions(&self) -> Result<impl Iterator<Item = MemoryRegion>, MemError> {
+        Err::<std::iter::Empty<MemoryRegion>, _>(MemError::Unsupported)
+    }
     #[cfg(windows)]
     /// Query a page of memory at address <addr>
     /// # Safety
@@ -128,7 +332,6 @@ pub trait Mem {
     /// Alter the protection of a memory region, needs implementation per platform
     /// # Safety
     /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
-
     unsafe fn alter_protection(
         &self,
         addr: usize,
@@ -138,17 +341,14 @@ pub trait Mem {
     /// Read raw bytes from memory at address <addr> with size <size>, needs implementation per platform
     /// # Safety
     /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
-
     unsafe fn raw_read(&self, addr: usize, data: *mut u8, size: usize) -> Result<(), MemError>;
     /// Write raw bytes to memory at address <addr> with size <size>, needs implementation per platform
     /// # Safety
     /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
-
     unsafe fn raw_write(&self, addr: usize, data: *const u8, size: usize) -> Result<(), MemError>;
     /// Allocate memory to process begninning at <addr> with size <size>, needs implementation per platform
     /// # Safety
     /// unsafe because it does direct calls to the OS. the address supplied could be invalid.
-
     unsafe fn raw_virtual_alloc(
         &self,
         addr: Option<usize>,
@@ -161,6 +361,129 @@ pub trait Mem {
     unsafe fn raw_virtual_free(&self, addr: usize, size: usize) -> Result<(), MemError>;
 }
 
+/// strings are read up to the next multiple of this, so reads never cross into the next page
+const STRING_CHUNK_SIZE: usize = 0x1000;
+
+/// types which are valid for any bit pattern and have no padding, so they can be read out of
+/// another process without producing invalid values.
+/// implemented for integers, floats and arrays of them, structs can use
+/// `#[derive(poggers_derive::Pod)]`.
+/// # Safety
+/// every bit pattern of the size of the type must be a valid value, and the type must not contain
+/// padding or pointers into this process.
+pub unsafe trait Pod: Copy + 'static {
+    /// get the bytes of the value
+    fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                std::mem::size_of::<Self>(),
+            )
+        }
+    }
+    /// create a value from <data>, none if it isn't exactly the size of the type
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() != std::mem::size_of::<Self>() {
+            return None;
+        }
+        Some(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+macro_rules! impl_pod {
+    ($($t:ty),*) => {
+        $(unsafe impl Pod for $t {})*
+    };
+}
+impl_pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+/// [Mem] implementations which can't affect the memory of this process when reading or writing,
+/// such as external processes. these get safe reads and writes of [Pod] types.
+/// ```rs
+/// let health = process.read_value::<f32>(player + 0x100)?;
+/// process.write_value(player + 0x100, &100f32)?;
+/// ```
+/// safe writes fail with [MemError::SelfWrite] when the target is this very process, use
+/// [Mem::write] for that instead.
+/// # Safety
+/// [Mem::raw_read] and [Mem::raw_write] must report invalid addresses as errors and never access
+/// the memory of this process outside of the given buffer, unless [SafeMem::is_this_process].
+pub unsafe trait SafeMem: Mem {
+    /// if reads and writes go to the memory of this process
+    fn is_this_process(&self) -> bool {
+        false
+    }
+    /// Read <T> from memory at address <addr>
+    fn read_value<T: Pod>(&self, addr: usize) -> Result<T, MemError> {
+        unsafe { self.read(addr) }
+    }
+    /// Read <count> consecutive <T> from memory at address <addr>
+    fn read_values<T: Pod>(&self, addr: usize, count: usize) -> Result<Vec<T>, MemError> {
+        let size = std::mem::size_of::<T>();
+        let total = count
+            .checked_mul(size)
+            .ok_or(MemError::ReadTooLarge { addr, count })?;
+        let data = unsafe { self.read_sized(addr, total)? };
+        Ok((0..count)
+            .map(|i| T::from_bytes(&data[i * size..(i + 1) * size]).unwrap())
+            .collect())
+    }
+    /// Write <T> to memory at address <addr>
+    fn write_value<T: Pod>(&self, addr: usize, value: &T) -> Result<(), MemError> {
+        if self.is_this_process() {
+            return Err(MemError::SelfWrite(addr));
+        }
+        unsafe { self.write_raw(addr, value.as_bytes()) }
+    }
+}
+
+/// the size of pointers in a process
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PointerWidth {
+    /// 4 byte pointers, i386 or WOW64 processes
+    Bits32,
+    /// 8 byte pointers
+    Bits64,
+}
+impl PointerWidth {
+    /// the pointer width of this build
+    #[cfg(target_pointer_width = "32")]
+    pub const NATIVE: Self = PointerWidth::Bits32;
+    /// the pointer width of this build
+    #[cfg(target_pointer_width = "64")]
+    pub const NATIVE: Self = PointerWidth::Bits64;
+    /// get the size of a pointer in bytes
+    pub const fn size(self) -> usize {
+        match self {
+            PointerWidth::Bits32 => 4,
+            PointerWidth::Bits64 => 8,
+        }
+    }
+    /// decode a pointer from the start of <data>, which must be at least [PointerWidth::size]
+    /// bytes
+    pub fn decode(self, data: &[u8]) -> usize {
+        match self {
+            PointerWidth::Bits32 => u32::from_ne_bytes(data[..4].try_into().unwrap()) as usize,
+            PointerWidth::Bits64 => u64::from_ne_bytes(data[..8].try_into().unwrap()) as usize,
+        }
+    }
+}
+
+/// a single read of a [Mem::read_scatter] batch
+#[derive(Debug)]
+pub struct ReadRequest<'a> {
+    /// the address to read from
+    pub addr: usize,
+    /// the buffer to read into, the whole buffer is filled
+    pub buf: &'a mut [u8],
+}
+impl<'a> ReadRequest<'a> {
+    /// create a request to read `buf.len()` bytes from <addr> into <buf>
+    pub fn new(addr: usize, buf: &'a mut [u8]) -> Self {
+        Self { addr, buf }
+    }
+}
+
 /// Mem-trait Failures
 #[derive(Debug, Error)]
 pub enum MemError {
@@ -170,6 +493,42 @@ pub enum MemError {
     /// Write failed
     #[error("Write failed [{0:X}]")]
     WriteFailure(usize),
+    /// Read failed, the OS reported <errno> (e.g. EPERM, ESRCH, EFAULT)
+    #[error("Read failed [{addr:X}]: {}", std::io::Error::from_raw_os_error(*.errno))]
+    ReadOsFailure {
+        /// address which was being read
+        addr: usize,
+        /// the errno reported by the OS
+        errno: i32,
+    },
+    /// Write failed, the OS reported <errno> (e.g. EPERM, ESRCH, EFAULT)
+    #[error("Write failed [{addr:X}]: {}", std::io::Error::from_raw_os_error(*.errno))]
+    WriteOsFailure {
+        /// address which was being written
+        addr: usize,
+        /// the errno reported by the OS
+        errno: i32,
+    },
+    /// Only part of the read completed, usually because the range crosses into an unmapped page
+    #[error("Partial read [{addr:X}]: {transferred:X}/{size:X} bytes")]
+    PartialRead {
+        /// address which was being read
+        addr: usize,
+        /// amount of bytes which were actually read
+        transferred: usize,
+        /// amount of bytes requested
+        size: usize,
+    },
+    /// Only part of the write completed, usually because the range crosses into an unmapped page
+    #[error("Partial write [{addr:X}]: {transferred:X}/{size:X} bytes")]
+    PartialWrite {
+        /// address which was being written
+        addr: usize,
+        /// amount of bytes which were actually written
+        transferred: usize,
+        /// amount of bytes requested
+        size: usize,
+    },
     /// Protection update failed
     #[error("Protection update to {1} failed [{0:X}]+{1:X}")]
     ProtectFailure(usize, usize, Protections),
@@ -179,11 +538,251 @@ pub enum MemError {
     /// Failed to free memory
     #[error("VirtualFree failed [{0:X}]+{1:X}")]
     FreeFailure(usize, usize),
+    /// Unable to query the memory regions of the process
+    #[error("Unable to query memory regions: {0}")]
+    QueryFailure(std::io::Error),
+    /// Reading a pointer chain failed at hop <hop> while reading the pointer at <addr>
+    #[error("Pointer chain failed at hop {hop} [{addr:X}]: {source}")]
+    ChainFailure {
+        /// zero based index of the offset which was being resolved
+        hop: usize,
+        /// address of the pointer which could not be read
+        addr: usize,
+        /// the error from reading the pointer
+        source: Box<MemError>,
+    },
+    /// The string at the address is not valid UTF-8/UTF-16 or is longer than allowed
+    #[error("Invalid string [{0:X}]")]
+    InvalidString(usize),
+    /// No memory region contains the address
+    #[error("No memory region contains [{0:X}]")]
+    NoRegion(usize),
     /// unsupported function for target os
     #[error("Unsupported")]
     Unsupported,
+    /// the size of a read of many values doesn't fit in a usize
+    #[error("Read of {count} values is too large [{addr:X}]")]
+    ReadTooLarge {
+        /// the address of the read
+        addr: usize,
+        /// the amount of values requested
+        count: usize,
+    },
+    /// a safe write would have changed the memory of this process
+    #[error("Safe write into this process [{0:X}]")]
+    SelfWrite(usize),
     // @todo(pozm): this needs to go and be replaced
     #[error("A Process Error Occurred: {0}")]
     /// Unable to get task
     ProcessError(#[from] ProcessError),
+    /// The module an address is relative to could not be found
+    #[error("Module error: {0}")]
+    ModuleError(#[from] ModuleError),
+    /// The pattern of a scan could not be parsed
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(#[from] PatternError),
+    /// The pattern of a scan was not found
+    #[error("Pattern not found")]
+    PatternNotFound,
+    /// The pattern of a scan which has to be unique matched more than once
+    #[error("Pattern is not unique, found {0} matches")]
+    PatternNotUnique(usize),
+}
+impl MemError {
+    /// get the errno reported by the OS, if this error came from a failed OS call
+    pub const fn errno(&self) -> Option<i32> {
+        match self {
+            MemError::ReadOsFailure { errno, .. } | MemError::WriteOsFailure { errno, .. } => {
+                Some(*errno)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mem, MemError, Pod, PointerWidth, SafeMem};
+    #[cfg(target_os = "linux")]
+    use crate::structures::process::Process;
+    use crate::{
+        structures::{expr::Expression, mock::MockProcess, pointer_scan::PointerScanner},
+        testing::{read_only, read_write},
+    };
+
+    fn process() -> MockProcess {
+        MockProcess::new("game")
+            .with_module("libgame.so", 0x400000, vec![0; 0x1000], read_only())
+            .with_region(0x10000, vec![0; 0x2000], read_write())
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_strings() {
+        let proc = Process::find_pid(std::process::id()).unwrap();
+        unsafe {
+            let pages = libc::mmap(
+                std::ptr::null_mut(),
+                0x2000,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            ) as usize;
+            libc::munmap((pages + 0x1000) as *mut libc::c_void, 0x1000);
+
+            // ends right before the unmapped page
+            let at = pages + 0x1000 - 6;
+            proc.write_c_string(at, "poggy").unwrap();
+            assert_eq!(proc.read_c_string(at, 0x100).unwrap(), "poggy");
+            assert_eq!(proc.read_c_string(at, 3).unwrap(), "pog");
+            assert_eq!(proc.read_os_string(at, 0x100).unwrap(), "poggy");
+            // runs into the unmapped page
+            proc.write_raw(at, b"poggy!").unwrap();
+            assert!(proc.read_c_string(at, 0x100).is_err());
+
+            proc.write_raw(pages, b"\xFFpog\0").unwrap();
+            assert!(matches!(
+                proc.read_c_string(pages, 0x100),
+                Err(MemError::InvalidString(_))
+            ));
+            assert_eq!(
+                proc.read_c_string_lossy(pages, 0x100).unwrap(),
+                "\u{FFFD}pog"
+            );
+
+            let at = pages + 0xFF0;
+            proc.write_wide_string(at, "p\u{F6}g").unwrap();
+            assert_eq!(proc.read_wide_string(at, 0x100).unwrap(), "p\u{F6}g");
+            proc.write_raw(at, &[0x00, 0xD8, 0x00, 0x00]).unwrap();
+            assert!(proc.read_wide_string(at, 0x100).is_err());
+            assert_eq!(proc.read_wide_string_lossy(at, 0x100).unwrap(), "\u{FFFD}");
+
+            proc.write(pages, &5u32).unwrap();
+            proc.write_raw(pages + 4, b"pogger").unwrap();
+            assert_eq!(
+                proc.read_prefixed_string::<u32>(pages, 0x100).unwrap(),
+                "pogge"
+            );
+            assert!(proc.read_prefixed_string::<u32>(pages, 4).is_err());
+
+            libc::munmap(pages as *mut libc::c_void, 0x1000);
+        }
+    }
+
+    #[derive(poggers_derive::RemoteStruct, Debug, PartialEq)]
+    struct Player {
+        #[offset(0x10)]
+        health: u32,
+        #[offset(0x18)]
+        position: [f32; 2],
+        #[offset(0x20)]
+        #[pointer]
+        team: u8,
+    }
+
+    #[test]
+    fn test_remote_struct() {
+        let proc = process();
+        let addr = 0x10000;
+        unsafe {
+            proc.write(addr + 0x10, &100u32).unwrap();
+            proc.write(addr + 0x18, &[1.0f32, 2.0]).unwrap();
+            proc.write(addr + 0x20, &0x11000usize).unwrap();
+            proc.write(0x11000, &3u8).unwrap();
+            assert_eq!(
+                Player::read_from(&proc, addr).unwrap(),
+                Player {
+                    health: 100,
+                    position: [1.0, 2.0],
+                    team: 3
+                }
+            );
+
+            Player::set_health(&proc, addr, &50).unwrap();
+            Player::set_team(&proc, addr, &4).unwrap();
+            assert_eq!(Player::get_health(&proc, addr).unwrap(), 50);
+            assert_eq!(proc.read::<u8>(0x11000).unwrap(), 4);
+
+            proc.write(addr + 0x20, &0x5000usize).unwrap();
+            assert!(Player::read_from(&proc, addr).is_err());
+        }
+    }
+
+    #[test]
+    fn test_pointer_width() {
+        let proc = MockProcess::new("game")
+            .with_pointer_width(PointerWidth::Bits32)
+            .with_module("game.exe", 0x400000, vec![0; 0x1000], read_write())
+            .with_region(0x10000, vec![0; 0x1000], read_write());
+        unsafe {
+            // the upper half of a 64-bit read would be garbage
+            proc.write(0x400010, &[0x10000u32, 0xFFFF_FFFF]).unwrap();
+            proc.write_ptr(0x10008, 0x10100).unwrap();
+            proc.write(0x1000C, &0xFFFF_FFFFu32).unwrap();
+            assert_eq!(proc.read_ptr(0x400010).unwrap(), 0x10000);
+
+            let target = proc
+                .address(0x400010)
+                .follow(&[0x8, 0x4])
+                .unwrap()
+                .get_addr();
+            assert_eq!(target, 0x10104);
+            let expr = Expression::parse("[[game.exe + 0x10] + 0x8] + 0x4").unwrap();
+            assert_eq!(expr.resolve(&proc).unwrap().get_addr(), target);
+
+            let results = PointerScanner::new(target)
+                .with_max_depth(2)
+                .with_max_offset(0x10)
+                .scan(&proc)
+                .unwrap();
+            let path = &results.paths()[0];
+            assert_eq!(path.get_module(), "game.exe");
+            assert_eq!(path.get_module_offset(), 0x10);
+            assert_eq!(path.get_offsets(), [0x8, 0x4]);
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, poggers_derive::Pod)]
+    #[repr(C)]
+    struct Vec3 {
+        x: f32,
+        y: f32,
+        z: f32,
+    }
+
+    #[test]
+    fn test_safe_reads() {
+        let proc = process();
+        let position = Vec3 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        };
+        proc.write_value(0x10000, &position).unwrap();
+        proc.write_value(0x10010, &[1u16, 2, 3]).unwrap();
+        assert_eq!(proc.read_value::<Vec3>(0x10000).unwrap(), position);
+        assert_eq!(proc.read_values::<u16>(0x10010, 3).unwrap(), [1, 2, 3]);
+        assert_eq!(proc.read_value::<[u16; 2]>(0x10012).unwrap(), [2, 3]);
+        assert!(proc.read_value::<u64>(0x5000).is_err());
+        assert!(proc.write_value(0x400000, &1u8).is_err());
+        assert_eq!(<[u8; 4]>::from_bytes(&[1, 2, 3]), None);
+        assert!(matches!(
+            proc.read_values::<u64>(0x10000, usize::MAX / 4),
+            Err(MemError::ReadTooLarge { addr: 0x10000, .. })
+        ));
+
+        #[cfg(target_os = "linux")]
+        {
+            let value = Box::new(1u32);
+            let addr = &*value as *const u32 as usize;
+            let this = Process::find_pid(std::process::id()).unwrap();
+            assert_eq!(this.read_value::<u32>(addr).unwrap(), 1);
+            assert!(matches!(
+                this.write_value(addr, &2u32),
+                Err(MemError::SelfWrite(at)) if at == addr
+            ));
+            assert_eq!(*value, 1);
+        }
+    }
 }